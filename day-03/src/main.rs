@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::env;
 use std::fs::read_to_string;
 use std::path::Path;
@@ -60,6 +60,36 @@ impl Fabric {
         }
         ids
     }
+    fn claims_at(&self, x: usize, y: usize) -> Option<&[u64]> {
+        if x >= self.sx || y >= self.sy {
+            return None;
+        }
+        Some(&self.area[x + y * self.sx])
+    }
+    // for every other claim that shares at least one square with `claim`,
+    // return the number of shared squares
+    fn overlapping_claims(&self, claim: &Claim) -> BTreeMap<u64, usize> {
+        let mut overlaps = BTreeMap::new();
+        for x in claim.px..(claim.px + claim.sx) {
+            for y in claim.py..(claim.py + claim.sy) {
+                for id in self.claims_at(x, y).unwrap_or(&[]) {
+                    if *id != claim.id {
+                        *overlaps.entry(*id).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        overlaps
+    }
+    // index is the number of claims on a square, value is the number of squares with that many claims
+    fn depth_histogram(&self) -> Vec<usize> {
+        let max_depth = self.area.iter().map(|cell| cell.len()).max().unwrap_or(0);
+        let mut histogram = vec![0; max_depth + 1];
+        for cell in &self.area {
+            histogram[cell.len()] += 1;
+        }
+        histogram
+    }
 }
 
 fn main() -> Result<(), String> {
@@ -87,6 +117,23 @@ fn main() -> Result<(), String> {
         println!("{id}");
     }
 
+    println!("Coverage depth histogram:");
+    for (depth, count) in fabric.depth_histogram().iter().enumerate() {
+        println!("{depth:>3}: {count}");
+    }
+
+    if let Some(claim_id) = env::args().nth(2) {
+        let claim_id: u64 = claim_id.parse().map_err(|e| format!("Invalid claim id: {e}"))?;
+        let claim = claims
+            .iter()
+            .find(|c| c.id == claim_id)
+            .ok_or_else(|| format!("No claim with id {claim_id}"))?;
+        println!("Claim #{claim_id} overlaps with:");
+        for (id, area) in fabric.overlapping_claims(claim) {
+            println!("#{id}: {area} squares");
+        }
+    }
+
     Ok(())
 }
 
@@ -115,6 +162,32 @@ fn parse_claim(line: &str) -> Option<Claim> {
 mod test {
     use super::*;
 
+    fn example_claims() -> [Claim; 3] {
+        [
+            Claim {
+                id: 1,
+                px: 1,
+                py: 3,
+                sx: 4,
+                sy: 4,
+            },
+            Claim {
+                id: 2,
+                px: 3,
+                py: 1,
+                sx: 4,
+                sy: 4,
+            },
+            Claim {
+                id: 3,
+                px: 5,
+                py: 5,
+                sx: 2,
+                sy: 2,
+            },
+        ]
+    }
+
     #[test]
     fn fabric_counts_overlapping_claims() {
         // given
@@ -151,6 +224,47 @@ mod test {
         assert_eq!(count, 4);
     }
 
+    #[test]
+    fn claims_at_returns_claims_covering_square() {
+        // given
+        let mut fabric = Fabric::with_size(8, 8);
+        fabric.process_claims(&example_claims());
+
+        // when/then
+        assert_eq!(fabric.claims_at(3, 3), Some(&[1, 2][..]));
+        assert_eq!(fabric.claims_at(5, 5), Some(&[3][..]));
+        assert_eq!(fabric.claims_at(0, 0), Some(&[][..]));
+        assert_eq!(fabric.claims_at(8, 0), None);
+    }
+
+    #[test]
+    fn overlapping_claims_reports_shared_area() {
+        // given
+        let mut fabric = Fabric::with_size(8, 8);
+        let claims = example_claims();
+        fabric.process_claims(&claims);
+
+        // when
+        let overlaps = fabric.overlapping_claims(&claims[0]);
+
+        // then
+        assert_eq!(overlaps, [(2, 4)].into_iter().collect());
+        assert!(fabric.overlapping_claims(&claims[2]).is_empty());
+    }
+
+    #[test]
+    fn depth_histogram_counts_squares_per_depth() {
+        // given
+        let mut fabric = Fabric::with_size(8, 8);
+        fabric.process_claims(&example_claims());
+
+        // when
+        let histogram = fabric.depth_histogram();
+
+        // then
+        assert_eq!(histogram, vec![64 - 28 - 4, 28, 4]);
+    }
+
     #[test]
     fn read_claim_reads_valid_claim() {
         // given
@@ -0,0 +1,386 @@
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Date {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn next_day(self) -> Date {
+        if self.day < days_in_month(self.year, self.month) {
+            Date {
+                day: self.day + 1,
+                ..self
+            }
+        } else if self.month < 12 {
+            Date {
+                year: self.year,
+                month: self.month + 1,
+                day: 1,
+            }
+        } else {
+            Date {
+                year: self.year + 1,
+                month: 1,
+                day: 1,
+            }
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// field order matters: the derived ordering is the chronological ordering
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Timestamp {
+    pub date: Date,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {:02}:{:02}", self.date, self.hour, self.minute)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Event {
+    Begin(u32),
+    FallsAsleep,
+    WakesUp,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LogEntry {
+    pub time: Timestamp,
+    pub event: Event,
+}
+
+// a guard's shift on a given date. Naps are half-open ranges of minutes of the midnight hour,
+// sleep outside of the midnight hour is not counted
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Shift {
+    pub guard: u32,
+    pub date: Date,
+    pub naps: Vec<(u32, u32)>,
+}
+
+// empty lines are skipped, all other lines must be valid entries
+pub fn parse_log(input: &str) -> Result<Vec<LogEntry>, String> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_log_line(line) {
+            Some(entry) => entries.push(entry),
+            None => errors.push(format!("line {}: invalid log entry '{}'", index + 1, line)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    entries.sort_by_key(|entry| entry.time);
+    Ok(entries)
+}
+
+pub fn parse_log_line(line: &str) -> Option<LogEntry> {
+    let (time, event) = line.split_once("] ")?;
+    let time = parse_timestamp(time.strip_prefix('[')?)?;
+    let event = match event {
+        "falls asleep" => Event::FallsAsleep,
+        "wakes up" => Event::WakesUp,
+        s => Event::Begin(
+            s.strip_prefix("Guard #")?
+                .strip_suffix(" begins shift")?
+                .parse()
+                .ok()?,
+        ),
+    };
+    Some(LogEntry { time, event })
+}
+
+fn parse_timestamp(s: &str) -> Option<Timestamp> {
+    let (date, time) = s.split_once(' ')?;
+    let mut date_parts = date.splitn(3, '-');
+    let year: u32 = date_parts.next()?.parse().ok()?;
+    let month: u32 = date_parts.next()?.parse().ok()?;
+    let day: u32 = date_parts.next()?.parse().ok()?;
+    let (hour, minute) = time.split_once(':')?;
+    let hour: u32 = hour.parse().ok()?;
+    let minute: u32 = minute.parse().ok()?;
+    if !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+    {
+        return None;
+    }
+    Some(Timestamp {
+        date: Date { year, month, day },
+        hour,
+        minute,
+    })
+}
+
+// a shift that begins in the evening belongs to the next day
+fn shift_date(begin: Timestamp) -> Date {
+    if begin.hour >= 12 {
+        begin.date.next_day()
+    } else {
+        begin.date
+    }
+}
+
+// map a timestamp to a minute of the midnight hour of the given date, clamping
+// everything before midnight to 0 and everything after 00:59 to 60
+fn midnight_minute(time: Timestamp, date: Date) -> u32 {
+    if time.date < date {
+        0
+    } else if time.date == date && time.hour == 0 {
+        time.minute
+    } else {
+        60
+    }
+}
+
+// expects the entries to be sorted chronologically. All problems of the log are reported, after
+// a problem the reconstruction goes on as if the offending entry was missing.
+pub fn reconstruct_shifts(entries: &[LogEntry]) -> Result<Vec<Shift>, String> {
+    let mut shifts: Vec<Shift> = Vec::new();
+    let mut dates: HashSet<Date> = HashSet::with_capacity(entries.len());
+    let mut asleep_since: Option<Timestamp> = None;
+    let mut errors: Vec<String> = Vec::new();
+    for entry in entries {
+        match entry.event {
+            Event::Begin(guard) => {
+                if let Some(since) = asleep_since.take() {
+                    errors.push(format!(
+                        "{}: guard #{} begins shift, but the previous guard is still asleep since {}",
+                        entry.time, guard, since
+                    ));
+                }
+                let date = shift_date(entry.time);
+                if !dates.insert(date) {
+                    errors.push(format!(
+                        "{}: guard #{} begins shift, but there already is a shift on {}",
+                        entry.time, guard, date
+                    ));
+                }
+                shifts.push(Shift {
+                    guard,
+                    date,
+                    naps: Vec::new(),
+                });
+            }
+            Event::FallsAsleep => {
+                if shifts.is_empty() {
+                    errors.push(format!("{}: falls asleep before any shift", entry.time));
+                } else if let Some(since) = asleep_since {
+                    errors.push(format!(
+                        "{}: falls asleep, but is already asleep since {}",
+                        entry.time, since
+                    ));
+                } else {
+                    asleep_since = Some(entry.time);
+                }
+            }
+            Event::WakesUp => {
+                let Some(shift) = shifts.last_mut() else {
+                    errors.push(format!("{}: wakes up before any shift", entry.time));
+                    continue;
+                };
+                let Some(since) = asleep_since.take() else {
+                    errors.push(format!(
+                        "{}: wakes up, but guard #{} is not asleep",
+                        entry.time, shift.guard
+                    ));
+                    continue;
+                };
+                let from = midnight_minute(since, shift.date);
+                let to = midnight_minute(entry.time, shift.date);
+                if from < to {
+                    shift.naps.push((from, to));
+                }
+            }
+        }
+    }
+    if let Some(since) = asleep_since {
+        errors.push(format!("log ends while a guard is asleep since {}", since));
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    Ok(shifts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_log_line_parses_correctly() {
+        let date = Date {
+            year: 1518,
+            month: 11,
+            day: 1,
+        };
+        assert_eq!(
+            parse_log_line("[1518-11-01 00:00] Guard #10 begins shift"),
+            Some(LogEntry {
+                time: Timestamp {
+                    date,
+                    hour: 0,
+                    minute: 0
+                },
+                event: Event::Begin(10)
+            })
+        );
+        assert_eq!(
+            parse_log_line("[1518-11-01 23:05] falls asleep"),
+            Some(LogEntry {
+                time: Timestamp {
+                    date,
+                    hour: 23,
+                    minute: 5
+                },
+                event: Event::FallsAsleep
+            })
+        );
+        assert_eq!(
+            parse_log_line("[1518-11-01 00:25] wakes up").map(|e| e.event),
+            Some(Event::WakesUp)
+        );
+        assert_eq!(parse_log_line("[1518-02-29 00:25] wakes up"), None);
+    }
+
+    #[test]
+    fn parse_log_sorts_chronologically() {
+        // given
+        let input = "[1518-11-02 00:40] falls asleep\n[1518-11-01 23:58] Guard #99 begins shift\n[1518-11-02 00:50] wakes up\n";
+
+        // when
+        let entries = parse_log(input).unwrap();
+
+        // then
+        let events: Vec<Event> = entries.into_iter().map(|e| e.event).collect();
+        assert_eq!(
+            events,
+            vec![Event::Begin(99), Event::FallsAsleep, Event::WakesUp]
+        );
+    }
+
+    #[test]
+    fn reconstruct_shifts_handles_shifts_starting_before_midnight() {
+        // given
+        let entries = parse_log(
+            "[1518-12-31 23:58] Guard #99 begins shift\n[1518-12-31 23:59] falls asleep\n[1519-01-01 00:10] wakes up\n[1519-01-01 00:45] falls asleep\n[1519-01-01 01:10] wakes up\n",
+        )
+        .unwrap();
+
+        // when
+        let shifts = reconstruct_shifts(&entries).unwrap();
+
+        // then
+        assert_eq!(
+            shifts,
+            vec![Shift {
+                guard: 99,
+                date: Date {
+                    year: 1519,
+                    month: 1,
+                    day: 1
+                },
+                naps: vec![(0, 10), (45, 60)]
+            }]
+        );
+    }
+
+    #[test]
+    fn reconstruct_shifts_rejects_invalid_logs() {
+        let overlapping = parse_log(
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 23:58] Guard #99 begins shift\n[1518-11-02 00:01] Guard #10 begins shift\n",
+        )
+        .unwrap();
+        assert!(reconstruct_shifts(&overlapping).is_err());
+
+        let double_sleep = parse_log(
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep\n[1518-11-01 00:07] falls asleep\n",
+        )
+        .unwrap();
+        assert!(reconstruct_shifts(&double_sleep).is_err());
+
+        let unterminated = parse_log(
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep\n[1518-11-02 00:00] Guard #99 begins shift\n",
+        )
+        .unwrap();
+        assert!(reconstruct_shifts(&unterminated).is_err());
+
+        let unterminated_at_end = parse_log(
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep\n",
+        )
+        .unwrap();
+        assert!(reconstruct_shifts(&unterminated_at_end).is_err());
+    }
+
+    #[test]
+    fn reconstruct_shifts_reports_every_problem() {
+        // given
+        let entries = parse_log(
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep\n[1518-11-01 00:07] falls asleep\n[1518-11-01 00:09] wakes up\n[1518-11-01 23:58] Guard #99 begins shift\n[1518-11-02 00:01] Guard #10 begins shift\n[1518-11-02 00:30] falls asleep\n",
+        )
+        .unwrap();
+
+        // when
+        let errors = reconstruct_shifts(&entries).unwrap_err();
+
+        // then
+        assert_eq!(
+            errors.lines().collect::<Vec<&str>>(),
+            vec![
+                "1518-11-01 00:07: falls asleep, but is already asleep since 1518-11-01 00:05",
+                "1518-11-02 00:01: guard #10 begins shift, but there already is a shift on 1518-11-02",
+                "log ends while a guard is asleep since 1518-11-02 00:30",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_log_reports_malformed_lines() {
+        // given
+        let input = "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep\n\n[1518-11-01 00:25] wakes uq\n[1518-11-01 00:3x] falls asleep\n";
+
+        // when
+        let errors = parse_log(input).unwrap_err();
+
+        // then
+        assert_eq!(
+            errors.lines().collect::<Vec<&str>>(),
+            vec![
+                "line 4: invalid log entry '[1518-11-01 00:25] wakes uq'",
+                "line 5: invalid log entry '[1518-11-01 00:3x] falls asleep'",
+            ]
+        );
+    }
+}
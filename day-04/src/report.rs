@@ -0,0 +1,209 @@
+use super::log::Shift;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct GuardReport {
+    pub guard: u32,
+    pub total_sleep: u32,
+    pub shifts: usize,
+    pub naps: usize,
+    // for each minute of the midnight hour: on how many shifts the guard was asleep
+    pub minutes: Vec<u32>,
+}
+
+impl GuardReport {
+    // the minute the guard was asleep most often and how often that was,
+    // the earliest minute wins on ties
+    pub fn sleepiest_minute(&self) -> Option<(u32, u32)> {
+        self.minutes
+            .iter()
+            .enumerate()
+            .filter(|(_, times)| **times > 0)
+            .max_by_key(|(minute, times)| (**times, std::cmp::Reverse(*minute)))
+            .map(|(minute, times)| (minute as u32, *times))
+    }
+
+    // probability that the guard is asleep in the sleepiest minute on any given shift
+    pub fn sleepiest_minute_probability(&self) -> Option<f64> {
+        let (_, times) = self.sleepiest_minute()?;
+        Some(times as f64 / self.shifts as f64)
+    }
+}
+
+// one report per guard, ordered by guard id
+pub fn guard_reports(shifts: &[Shift]) -> Vec<GuardReport> {
+    let mut reports: BTreeMap<u32, GuardReport> = BTreeMap::new();
+    for shift in shifts {
+        let report = reports.entry(shift.guard).or_insert_with(|| GuardReport {
+            guard: shift.guard,
+            total_sleep: 0,
+            shifts: 0,
+            naps: 0,
+            minutes: vec![0; 60],
+        });
+        report.shifts += 1;
+        report.naps += shift.naps.len();
+        for (from, to) in &shift.naps {
+            report.total_sleep += to - from;
+            for minute in *from..*to {
+                report.minutes[minute as usize] += 1;
+            }
+        }
+    }
+    reports.into_values().collect()
+}
+
+pub fn reports_csv(reports: &[GuardReport]) -> String {
+    let mut csv = String::from(
+        "guard,total_sleep,shifts,naps,sleepiest_minute,times_asleep_in_minute,probability\n",
+    );
+    for report in reports {
+        let (minute, times, probability) = match (
+            report.sleepiest_minute(),
+            report.sleepiest_minute_probability(),
+        ) {
+            (Some((minute, times)), Some(probability)) => (
+                minute.to_string(),
+                times.to_string(),
+                format!("{:.3}", probability),
+            ),
+            _ => (String::new(), String::new(), String::new()),
+        };
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{}",
+            report.guard,
+            report.total_sleep,
+            report.shifts,
+            report.naps,
+            minute,
+            times,
+            probability
+        )
+        .expect("writing to a string does not fail");
+    }
+    csv
+}
+
+// 60 characters, one for each minute of the midnight hour: '#' means asleep, '.' means awake
+pub fn shift_timeline(shift: &Shift) -> String {
+    let mut timeline = vec!['.'; 60];
+    for (from, to) in &shift.naps {
+        for minute in *from..*to {
+            timeline[minute as usize] = '#';
+        }
+    }
+    timeline.into_iter().collect()
+}
+
+// renders the shifts like the table in the puzzle description
+pub fn timeline_chart(shifts: &[Shift]) -> String {
+    let id_width = shifts
+        .iter()
+        .map(|shift| shift.guard.to_string().len() + 1)
+        .max()
+        .unwrap_or(2)
+        .max(2);
+    let tens: String = (0..60).map(|minute| (b'0' + minute / 10) as char).collect();
+    let ones: String = (0..60).map(|minute| (b'0' + minute % 10) as char).collect();
+
+    let mut chart = String::new();
+    writeln!(chart, "Date   {:<id_width$}  Minute", "ID")
+        .expect("writing to a string does not fail");
+    writeln!(chart, "       {:id_width$}  {}", "", tens)
+        .expect("writing to a string does not fail");
+    writeln!(chart, "       {:id_width$}  {}", "", ones)
+        .expect("writing to a string does not fail");
+    for shift in shifts {
+        writeln!(
+            chart,
+            "{:02}-{:02}  {:<id_width$}  {}",
+            shift.date.month,
+            shift.date.day,
+            format!("#{}", shift.guard),
+            shift_timeline(shift)
+        )
+        .expect("writing to a string does not fail");
+    }
+    chart
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::log::{parse_log, reconstruct_shifts};
+    use super::*;
+
+    const EXAMPLE_LOG: &str = "[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up
+";
+
+    fn example_shifts() -> Vec<Shift> {
+        reconstruct_shifts(&parse_log(EXAMPLE_LOG).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn guard_reports_works_for_example() {
+        // when
+        let reports = guard_reports(&example_shifts());
+
+        // then
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].guard, 10);
+        assert_eq!(reports[0].total_sleep, 50);
+        assert_eq!(reports[0].shifts, 2);
+        assert_eq!(reports[0].naps, 3);
+        assert_eq!(reports[0].sleepiest_minute(), Some((24, 2)));
+        assert_eq!(reports[0].sleepiest_minute_probability(), Some(1.0));
+        assert_eq!(reports[1].guard, 99);
+        assert_eq!(reports[1].sleepiest_minute(), Some((45, 3)));
+        assert_eq!(reports[1].total_sleep, 30);
+    }
+
+    #[test]
+    fn reports_csv_writes_one_line_per_guard() {
+        // when
+        let csv = reports_csv(&guard_reports(&example_shifts()));
+
+        // then
+        assert_eq!(
+            csv,
+            "guard,total_sleep,shifts,naps,sleepiest_minute,times_asleep_in_minute,probability\n10,50,2,3,24,2,1.000\n99,30,3,3,45,3,1.000\n"
+        );
+    }
+
+    #[test]
+    fn timeline_chart_matches_puzzle_table() {
+        // when
+        let chart = timeline_chart(&example_shifts());
+
+        // then
+        assert_eq!(
+            chart,
+            "Date   ID   Minute
+            000000000011111111112222222222333333333344444444445555555555
+            012345678901234567890123456789012345678901234567890123456789
+11-01  #10  .....####################.....#########################.....
+11-02  #99  ........................................##########..........
+11-03  #10  ........................#####...............................
+11-04  #99  ....................................##########..............
+11-05  #99  .............................................##########.....
+"
+        );
+    }
+}
@@ -1,27 +1,25 @@
-use std::collections::HashSet;
+mod rules;
+
+use self::rules::{parse_pair_table, react, OppositeCase, Reaction, ReactionRules, Reactor};
+use std::collections::BTreeMap;
 use std::env;
-use std::fs::File;
-use std::io::{BufReader, Read};
+use std::fs::{read_to_string, File};
+use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 
 fn main() -> Result<(), String> {
     let filename = env::args().nth(1).ok_or("No file name given.".to_owned())?;
-    let content = read_file(&Path::new(&filename)).map_err(|e| e.to_string())?;
-    let trimmed = content.trim();
+    let file = File::open(Path::new(&filename)).map_err(|e| e.to_string())?;
 
-    let reacted = react_polymer(trimmed, None);
+    let (reacted, without_unit) =
+        react_stream(&OppositeCase, BufReader::new(file)).map_err(|e| e.to_string())?;
 
     println!(
         "After reacting, the polymer has a length of {} units",
-        reacted.chars().count()
+        reacted.len()
     );
 
-    let units = available_units(trimmed);
-
-    let shortest = units
-        .iter()
-        .map(|unit| react_polymer(trimmed, Some(*unit)).chars().count())
-        .min();
+    let shortest = without_unit.values().min();
     if let Some(length) = shortest {
         println!(
             "After removing one unit type, the shortest reacted polymer has a length of {}.",
@@ -29,71 +27,137 @@ fn main() -> Result<(), String> {
         );
     }
 
+    // optionally show how the polymer reacts, either with the puzzle rules or with
+    // the reacting pairs from a rule file
+    if let Some(mode) = env::args().nth(2) {
+        let polymer = read_to_string(Path::new(&filename)).map_err(|e| e.to_string())?;
+        let polymer = polymer.trim();
+        let reaction = if mode == "trace" {
+            react(&OppositeCase, polymer)
+        } else {
+            let rules = read_to_string(Path::new(&mode)).map_err(|e| e.to_string())?;
+            react(&parse_pair_table(&rules)?, polymer)
+        };
+        print_reaction(&reaction);
+    }
+
     Ok(())
 }
 
-fn read_file(path: &Path) -> std::io::Result<String> {
-    let ifile = File::open(path)?;
-    let mut bufr = BufReader::new(ifile);
-    let mut result = String::with_capacity(2048);
-    bufr.read_to_string(&mut result)?;
-    return Ok(result);
+fn print_reaction(reaction: &Reaction) {
+    for (step, annihilation) in reaction.trace.iter().enumerate() {
+        println!(
+            "{:>6}: {} (unit {}) and {} (unit {}) annihilate",
+            step + 1,
+            annihilation.left.1,
+            annihilation.left.0,
+            annihilation.right.1,
+            annihilation.right.0
+        );
+    }
+    println!(
+        "Reacted polymer ({} units): {}",
+        reaction.units.len(),
+        reaction.units.concat()
+    );
 }
 
-fn available_units(pol: &str) -> HashSet<char> {
-    pol.chars().map(|c| c.to_ascii_lowercase()).collect()
-}
+// a unit of a streamed polymer, only ASCII letters are units
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct AsciiUnit(u8);
 
-fn react_polymer(pol: &str, filter_element: Option<char>) -> String {
-    let mut stack: Vec<char> = Vec::with_capacity(pol.len());
-    for c in pol.chars().filter(|c| {
-        filter_element
-            .map(|f| c.to_ascii_lowercase() != f)
-            .unwrap_or(true)
-    }) {
-        if chars_match(stack.last().map(|c| c.clone()), c) {
-            stack.pop();
-        } else {
-            stack.push(c);
-        }
+impl AsRef<str> for AsciiUnit {
+    fn as_ref(&self) -> &str {
+        std::str::from_utf8(std::slice::from_ref(&self.0)).expect("ASCII letters are valid UTF-8")
     }
-    return stack.iter().collect();
 }
 
-fn chars_match(opt_left: Option<char>, right: char) -> bool {
-    if let Some(left) = opt_left {
-        return left.to_ascii_lowercase() == right.to_ascii_lowercase()
-            && (left.is_uppercase() && right.is_lowercase()
-                || left.is_lowercase() && right.is_uppercase());
+// reacts the polymer in a single pass. Also returns, for every unit type (lowercase) that occurs
+// in the polymer, the length of the reacted polymer with that unit type removed.
+// Whitespace is ignored, any other non-letter is an error.
+fn react_stream<'r, R: ReactionRules + ?Sized, B: BufRead>(
+    rules: &'r R,
+    mut reader: B,
+) -> io::Result<(Reactor<'r, R, AsciiUnit>, BTreeMap<u8, usize>)> {
+    let mut reactor = Reactor::new(rules);
+    let mut without_unit: BTreeMap<u8, Reactor<R, AsciiUnit>> = BTreeMap::new();
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        for unit in buf {
+            if unit.is_ascii_whitespace() {
+                continue;
+            }
+            if !unit.is_ascii_alphabetic() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid unit in polymer: {:?}", *unit as char),
+                ));
+            }
+            let unit_type = unit.to_ascii_lowercase();
+            // all units so far were of other types, so the reactor without this type
+            // is exactly the same as the reactor with all units up to here
+            without_unit
+                .entry(unit_type)
+                .or_insert_with(|| reactor.clone());
+            for (removed, filtered) in without_unit.iter_mut() {
+                if *removed != unit_type {
+                    filtered.add_unit(AsciiUnit(*unit));
+                }
+            }
+            reactor.add_unit(AsciiUnit(*unit));
+        }
+        let consumed = buf.len();
+        reader.consume(consumed);
     }
-    return false;
+    let lengths = without_unit
+        .into_iter()
+        .map(|(unit_type, filtered)| (unit_type, filtered.len()))
+        .collect();
+    Ok((reactor, lengths))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    fn polymer(reactor: &Reactor<OppositeCase, AsciiUnit>) -> String {
+        reactor.units().map(|unit| unit.as_ref()).collect()
+    }
+
+    #[test]
+    fn react_stream_works_correctly() {
+        // given
+        let input = "dabAcCaCBAcCcaDA\n";
+
+        // when
+        let (reacted, without_unit) = react_stream(&OppositeCase, input.as_bytes()).unwrap();
+
+        // then
+        assert_eq!(polymer(&reacted), "dabCBAcaDA");
+        assert_eq!(
+            without_unit,
+            BTreeMap::from([(b'a', 6), (b'b', 8), (b'c', 4), (b'd', 6)])
+        );
+    }
+
     #[test]
-    fn react_polymer_works_correctly() {
+    fn react_stream_reacts_across_buffer_boundaries() {
         // given
-        let input = "dabAcCaCBAcCcaDA";
+        let reader = BufReader::with_capacity(3, "dabAcCaCBAcCcaDA".as_bytes());
 
         // when
-        let result = react_polymer(input, None);
+        let (reacted, without_unit) = react_stream(&OppositeCase, reader).unwrap();
 
         // then
-        assert_eq!(&result, "dabCBAcaDA");
+        assert_eq!(polymer(&reacted), "dabCBAcaDA");
+        assert_eq!(without_unit.get(&b'c'), Some(&4));
     }
 
     #[test]
-    fn chars_match_works_correctly() {
-        assert!(!chars_match(None, 'a'));
-        assert!(!chars_match(Some('b'), 'a'));
-        assert!(!chars_match(Some('B'), 'a'));
-        assert!(!chars_match(Some('b'), 'A'));
-        assert!(!chars_match(Some('a'), 'a'));
-        assert!(!chars_match(Some('A'), 'A'));
-        assert!(chars_match(Some('A'), 'a'));
-        assert!(chars_match(Some('a'), 'A'));
+    fn react_stream_rejects_invalid_units() {
+        assert!(react_stream(&OppositeCase, "aB3".as_bytes()).is_err());
     }
 }
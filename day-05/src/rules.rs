@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+
+pub trait ReactionRules {
+    // split a polymer into its units, by default every char is a unit
+    fn split_units<'a>(&self, polymer: &'a str) -> Vec<&'a str> {
+        polymer
+            .char_indices()
+            .map(|(i, c)| &polymer[i..i + c.len_utf8()])
+            .collect()
+    }
+
+    fn reacts(&self, left: &str, right: &str) -> bool;
+}
+
+// the puzzle rules: the same letter in opposite case reacts. Works for every alphabet that has
+// upper and lower case, not only for ASCII
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct OppositeCase;
+
+impl ReactionRules for OppositeCase {
+    fn reacts(&self, left: &str, right: &str) -> bool {
+        let mut left_chars = left.chars();
+        let mut right_chars = right.chars();
+        match (
+            left_chars.next(),
+            left_chars.next(),
+            right_chars.next(),
+            right_chars.next(),
+        ) {
+            (Some(l), None, Some(r), None) => {
+                (l.is_uppercase() && r.is_lowercase() || l.is_lowercase() && r.is_uppercase())
+                    && l.to_lowercase().eq(r.to_lowercase())
+            }
+            _ => false,
+        }
+    }
+}
+
+// arbitrary (multi-character) units that react in pairs. A pair reacts regardless of which
+// unit comes first. Parts of a polymer that are not known units are split into single chars
+// that react with nothing
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PairTable {
+    pairs: HashSet<(String, String)>,
+    // longest first, so splitting prefers longer units
+    units: Vec<String>,
+}
+
+impl PairTable {
+    pub fn new(pairs: &[(&str, &str)]) -> PairTable {
+        let mut units: Vec<String> = pairs
+            .iter()
+            .flat_map(|(l, r)| [l.to_string(), r.to_string()])
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect();
+        units.sort_unstable_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        PairTable {
+            pairs: pairs
+                .iter()
+                .map(|(l, r)| (l.to_string(), r.to_string()))
+                .collect(),
+            units,
+        }
+    }
+}
+
+impl ReactionRules for PairTable {
+    fn split_units<'a>(&self, polymer: &'a str) -> Vec<&'a str> {
+        let mut result = Vec::with_capacity(polymer.len());
+        let mut rest = polymer;
+        while let Some(c) = rest.chars().next() {
+            let len = self
+                .units
+                .iter()
+                .find(|unit| rest.starts_with(unit.as_str()))
+                .map(|unit| unit.len())
+                .unwrap_or_else(|| c.len_utf8());
+            result.push(&rest[..len]);
+            rest = &rest[len..];
+        }
+        result
+    }
+
+    fn reacts(&self, left: &str, right: &str) -> bool {
+        // HashSet<(String, String)> cannot be queried with (&str, &str), but the tables are small
+        self.pairs
+            .iter()
+            .any(|(l, r)| l == left && r == right || l == right && r == left)
+    }
+}
+
+// one line per pair, the two units are separated by whitespace
+pub fn parse_pair_table(input: &str) -> Result<PairTable, String> {
+    let pairs = input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(l), Some(r), None) => Ok((l, r)),
+                _ => Err(format!("Invalid reaction pair: '{line}'")),
+            }
+        })
+        .collect::<Result<Vec<(&str, &str)>, String>>()?;
+    Ok(PairTable::new(&pairs))
+}
+
+// two units (with their index in the original polymer) that annihilated each other
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Annihilation<'a> {
+    pub left: (usize, &'a str),
+    pub right: (usize, &'a str),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Reaction<'a> {
+    pub units: Vec<&'a str>,
+    pub trace: Vec<Annihilation<'a>>,
+}
+
+// reacts a polymer unit by unit, so only the reacted polymer has to be kept in memory. Every
+// unit is kept with its index in the polymer.
+#[derive(Debug)]
+pub struct Reactor<'r, R: ?Sized, U> {
+    rules: &'r R,
+    n_units: usize,
+    stack: Vec<(usize, U)>,
+}
+
+// derive would require R: Clone, but only the reference is cloned
+impl<R: ?Sized, U: Clone> Clone for Reactor<'_, R, U> {
+    fn clone(&self) -> Self {
+        Reactor {
+            rules: self.rules,
+            n_units: self.n_units,
+            stack: self.stack.clone(),
+        }
+    }
+}
+
+impl<'r, R: ReactionRules + ?Sized, U: AsRef<str>> Reactor<'r, R, U> {
+    pub fn new(rules: &'r R) -> Self {
+        Reactor {
+            rules,
+            n_units: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    // adds the next unit of the polymer. If it reacts with the unit before it, both units are
+    // returned with their indices.
+    pub fn add_unit(&mut self, unit: U) -> Option<((usize, U), (usize, U))> {
+        let index = self.n_units;
+        self.n_units += 1;
+        match self.stack.last() {
+            Some((_, left)) if self.rules.reacts(left.as_ref(), unit.as_ref()) => {
+                let left = self.stack.pop().expect("the stack is not empty");
+                Some((left, (index, unit)))
+            }
+            _ => {
+                self.stack.push((index, unit));
+                None
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn units(&self) -> impl Iterator<Item = &U> {
+        self.stack.iter().map(|(_, unit)| unit)
+    }
+}
+
+pub fn react<'a, R: ReactionRules + ?Sized>(rules: &R, polymer: &'a str) -> Reaction<'a> {
+    let mut reactor = Reactor::new(rules);
+    let mut trace = Vec::new();
+    for unit in rules.split_units(polymer) {
+        if let Some((left, right)) = reactor.add_unit(unit) {
+            trace.push(Annihilation { left, right });
+        }
+    }
+    Reaction {
+        units: reactor.units().copied().collect(),
+        trace,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn react_with_opposite_case_works_for_example_and_non_ascii() {
+        // when
+        let reaction = react(&OppositeCase, "dabAcCaCBAcCcaDA");
+
+        // then
+        assert_eq!(reaction.units.concat(), "dabCBAcaDA");
+        assert_eq!(
+            reaction.trace[..2],
+            [
+                Annihilation {
+                    left: (4, "c"),
+                    right: (5, "C")
+                },
+                Annihilation {
+                    left: (3, "A"),
+                    right: (6, "a")
+                }
+            ]
+        );
+        assert_eq!(react(&OppositeCase, "äÖöΣσäx").units.concat(), "ääx");
+    }
+
+    #[test]
+    fn opposite_case_reacts_only_for_same_letter_in_opposite_case() {
+        assert!(!OppositeCase.reacts("b", "a"));
+        assert!(!OppositeCase.reacts("B", "a"));
+        assert!(!OppositeCase.reacts("b", "A"));
+        assert!(!OppositeCase.reacts("a", "a"));
+        assert!(!OppositeCase.reacts("A", "A"));
+        assert!(OppositeCase.reacts("A", "a"));
+        assert!(OppositeCase.reacts("a", "A"));
+    }
+
+    #[test]
+    fn react_with_pair_table_supports_multi_char_units() {
+        // given
+        let rules = parse_pair_table("Na Cl\nH OH\n").unwrap();
+
+        // when
+        let reaction = react(&rules, "NaHOHClxH");
+
+        // then
+        assert_eq!(reaction.units, vec!["x", "H"]);
+        assert_eq!(
+            reaction.trace,
+            vec![
+                Annihilation {
+                    left: (1, "H"),
+                    right: (2, "OH")
+                },
+                Annihilation {
+                    left: (0, "Na"),
+                    right: (3, "Cl")
+                }
+            ]
+        );
+        assert!(parse_pair_table("Na\n").is_err());
+    }
+}
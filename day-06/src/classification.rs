@@ -0,0 +1,161 @@
+use super::voronoi::{closest_points, Metric, Point};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AreaClass {
+    pub point: Point,
+    // None if the area is infinite
+    pub size: Option<usize>,
+    // cells within the bounding box where this point is one of several closest points
+    pub tie_cells: Vec<Point>,
+}
+
+impl AreaClass {
+    pub fn is_finite(&self) -> bool {
+        self.size.is_some()
+    }
+}
+
+// classify the manhattan areas of all points
+pub fn classify_areas(points: &[Point]) -> Vec<AreaClass> {
+    let unbounded = unbounded_areas(points);
+    let mut classes: Vec<AreaClass> = points
+        .iter()
+        .zip(&unbounded)
+        .map(|(point, unbounded)| AreaClass {
+            point: *point,
+            size: if *unbounded { None } else { Some(0) },
+            tie_cells: Vec::new(),
+        })
+        .collect();
+    if points.is_empty() {
+        return classes;
+    }
+
+    // a cell outside of the bounding box has the same closest points as the cell on the border
+    // next to it, so finite areas lie completely within the bounding box
+    let (min, max) = bounding_box(points);
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let cell = Point { x, y };
+            let closest = closest_points(points, cell, Metric::Manhattan);
+            if closest.len() == 1 {
+                if let Some(size) = classes[closest[0]].size.as_mut() {
+                    *size += 1;
+                }
+            } else {
+                for index in closest {
+                    classes[index].tie_cells.push(cell);
+                }
+            }
+        }
+    }
+    classes
+}
+
+type Projection = fn(&Point) -> i64;
+
+// Which points have an infinite area? For the euclidean metric, these would be exactly the
+// points on the convex hull, but this is not true for the manhattan metric (a point inside the
+// hull can win a whole column, see the tests). Instead, we look at what happens far away:
+// for a cell (X, y) with X > max_x, the distance to point i is X + (|y - y_i| - x_i),
+// so its closest point only depends on y, and the area of the unique minimizer of |y - y_i| - x_i
+// reaches infinitely far to the right. For y outside of [min_y, max_y], the minimizer is the same
+// as for min_y or max_y, so checking all rows (and columns for the other sides) of the bounding box
+// is enough. Any infinite area must contain cells beyond one of the sides, so this is exact.
+pub fn unbounded_areas(points: &[Point]) -> Vec<bool> {
+    let mut unbounded = vec![false; points.len()];
+    if points.is_empty() {
+        return unbounded;
+    }
+    let (min, max) = bounding_box(points);
+    // (far coordinate, offset coordinate, offset range) for each side
+    let sides: [(Projection, Projection, i64, i64); 4] = [
+        (|p| p.x, |p| p.y, min.y, max.y),
+        (|p| -p.x, |p| p.y, min.y, max.y),
+        (|p| p.y, |p| p.x, min.x, max.x),
+        (|p| -p.y, |p| p.x, min.x, max.x),
+    ];
+    for (far, offset, from, to) in sides {
+        for t in from..=to {
+            let keys = points.iter().map(|p| (t - offset(p)).abs() - far(p));
+            if let Some(index) = unique_min(keys) {
+                unbounded[index] = true;
+            }
+        }
+    }
+    unbounded
+}
+
+fn unique_min(values: impl Iterator<Item = i64>) -> Option<usize> {
+    let mut best: Option<(usize, i64)> = None;
+    let mut tie = false;
+    for (index, value) in values.enumerate() {
+        match best {
+            Some((_, b)) if value > b => (),
+            Some((_, b)) if value == b => tie = true,
+            _ => {
+                best = Some((index, value));
+                tie = false;
+            }
+        }
+    }
+    if tie {
+        None
+    } else {
+        best.map(|(index, _)| index)
+    }
+}
+
+fn bounding_box(points: &[Point]) -> (Point, Point) {
+    let min = Point {
+        x: points.iter().map(|p| p.x).min().unwrap_or(0),
+        y: points.iter().map(|p| p.y).min().unwrap_or(0),
+    };
+    let max = Point {
+        x: points.iter().map(|p| p.x).max().unwrap_or(0),
+        y: points.iter().map(|p| p.y).max().unwrap_or(0),
+    };
+    (min, max)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn points(coords: &[(i64, i64)]) -> Vec<Point> {
+        coords.iter().map(|(x, y)| Point { x: *x, y: *y }).collect()
+    }
+
+    #[test]
+    fn classify_areas_works_for_example() {
+        // given
+        let points = points(&[(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)]);
+
+        // when
+        let classes = classify_areas(&points);
+
+        // then
+        let finite: Vec<bool> = classes.iter().map(|c| c.is_finite()).collect();
+        assert_eq!(finite, vec![false, false, false, true, true, false]);
+        assert_eq!(classes[3].size, Some(9));
+        assert_eq!(classes[4].size, Some(17));
+        // one of the dots between area B and area D in the puzzle description
+        assert!(classes[1].tie_cells.contains(&Point { x: 1, y: 4 }));
+        assert!(classes[3].tie_cells.contains(&Point { x: 1, y: 4 }));
+        assert!(!classes[4].tie_cells.contains(&Point { x: 1, y: 4 }));
+    }
+
+    #[test]
+    fn unbounded_areas_detects_infinite_area_inside_convex_hull() {
+        // given
+        // the last point is strictly inside the triangle of the other three points,
+        // but it is the closest point for the cells far below it
+        let points = points(&[(0, 0), (10, 0), (5, 10), (5, 1)]);
+
+        // when
+        let unbounded = unbounded_areas(&points);
+
+        // then
+        assert_eq!(unbounded, vec![true, true, true, true]);
+    }
+}
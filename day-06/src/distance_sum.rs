@@ -0,0 +1,93 @@
+use super::voronoi::Point;
+
+// number of cells whose sum of manhattan distances to all points is less than max_distance.
+// The sum splits into a sum over x distances and a sum over y distances, so we only need to
+// compute these sums once per column and once per row and can then count the matching pairs.
+pub fn safe_region_size(points: &[Point], max_distance: i64) -> usize {
+    if points.is_empty() {
+        // every cell would be in the region
+        return 0;
+    }
+    let x_sums = axis_distance_sums(points.iter().map(|p| p.x).collect(), max_distance);
+    let mut y_sums = axis_distance_sums(points.iter().map(|p| p.y).collect(), max_distance);
+    y_sums.sort_unstable();
+    x_sums
+        .iter()
+        .map(|x_sum| y_sums.partition_point(|y_sum| x_sum + y_sum < max_distance))
+        .sum()
+}
+
+// for every position on one axis, the sum of distances to all values, as long as this sum is
+// less than max_distance (other positions cannot be part of the region anyway)
+fn axis_distance_sums(mut values: Vec<i64>, max_distance: i64) -> Vec<i64> {
+    values.sort_unstable();
+    let n = values.len() as i64;
+    let mut prefix_sums = Vec::with_capacity(values.len() + 1);
+    prefix_sums.push(0);
+    for v in &values {
+        prefix_sums.push(prefix_sums[prefix_sums.len() - 1] + v);
+    }
+    let total = prefix_sums[values.len()];
+
+    // outside of [min, max], every step adds n to the sum
+    let margin = max_distance / n + 1;
+    let from = values[0] - margin;
+    let to = values[values.len() - 1] + margin;
+    (from..=to)
+        .map(|pos| {
+            let below = values.partition_point(|v| *v < pos);
+            let below_sum = pos * below as i64 - prefix_sums[below];
+            let above_sum = (total - prefix_sums[below]) - pos * (n - below as i64);
+            below_sum + above_sum
+        })
+        .filter(|sum| *sum < max_distance)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn brute_force(points: &[Point], max_distance: i64) -> usize {
+        let margin = max_distance / points.len() as i64 + 1;
+        let mut count = 0;
+        for x in -200 - margin..=200 + margin {
+            for y in -200 - margin..=200 + margin {
+                let sum: i64 = points
+                    .iter()
+                    .map(|p| (p.x - x).abs() + (p.y - y).abs())
+                    .sum();
+                if sum < max_distance {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn safe_region_size_works_for_example() {
+        let points: Vec<Point> = [(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)]
+            .iter()
+            .map(|(x, y)| Point { x: *x, y: *y })
+            .collect();
+        assert_eq!(safe_region_size(&points, 32), 16);
+    }
+
+    #[test]
+    fn safe_region_size_matches_brute_force_with_negative_coordinates() {
+        // given
+        let points: Vec<Point> = [(-17, 3), (40, -12), (-5, -30), (22, 25), (0, 0), (-40, 18)]
+            .iter()
+            .map(|(x, y)| Point { x: *x, y: *y })
+            .collect();
+
+        // when/then
+        for max_distance in [0, 150, 200, 333, 600] {
+            assert_eq!(
+                safe_region_size(&points, max_distance),
+                brute_force(&points, max_distance)
+            );
+        }
+    }
+}
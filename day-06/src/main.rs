@@ -1,3 +1,21 @@
+// the original solution is kept as it was written, the newer modules are checked against it
+#![allow(
+    suspicious_double_ref_op,
+    clippy::clone_on_copy,
+    clippy::identity_op,
+    clippy::manual_flatten,
+    clippy::needless_borrow,
+    clippy::needless_return,
+    clippy::write_with_newline
+)]
+
+mod classification;
+mod distance_sum;
+mod voronoi;
+
+use self::classification::classify_areas;
+use self::distance_sum::safe_region_size;
+use self::voronoi::{parse_points, Metric, Point, Regions};
 use std::collections::HashSet;
 use std::env;
 use std::fmt;
@@ -30,65 +48,66 @@ fn main() -> Result<(), String> {
         println!("There are no finite areas");
     }
 
+    // the border heuristic above is not exact in general, so double-check it
+    let points = parse_points(&lines);
+    let classes = classify_areas(&points);
+    for (index, class) in classes.iter().enumerate() {
+        if class.is_finite() == infinite_areas.contains(&index) {
+            println!(
+                "Warning: area {} at {:?} is {} but the border heuristic says otherwise",
+                index,
+                class.point,
+                if class.is_finite() {
+                    "finite"
+                } else {
+                    "infinite"
+                }
+            );
+        }
+    }
+    if let Some((index, class)) = classes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, class)| class.size.map(|size| (index, class, size)))
+        .max_by_key(|(_, _, size)| *size)
+        .map(|(index, class, _)| (index, class))
+    {
+        println!(
+            "Area {} has {} cells that are tied with other areas",
+            index,
+            class.tie_cells.len()
+        );
+    }
+
     let puzzle2_area = area_with_max_distance_sum(&coords, 10000);
     println!("Solution for puzzle 2: {}", puzzle2_area);
 
+    if let Some(metric_name) = env::args().nth(2) {
+        let metric =
+            Metric::parse(&metric_name).ok_or_else(|| format!("Unknown metric: {metric_name}"))?;
+        let regions = Regions::compute(&points, metric);
+        if let Some((index, size)) = regions.largest_finite_area() {
+            println!(
+                "With the {:?} metric, area {} is the largest finite area with a size of {}",
+                metric, index, size
+            );
+        } else {
+            println!("With the {:?} metric, there are no finite areas", metric);
+        }
+    }
+
     Ok(())
 }
 
-fn area_with_max_distance_sum(coords_param: &[Coord], max_distance: usize) -> usize {
-    // Great: I have usize everywhere, but we may need negative numbers here
-    // because I will never need this code again, I can just shift all the coordinates instead...
-    let coords: Vec<Coord> = coords_param
+fn area_with_max_distance_sum(coords: &[Coord], max_distance: usize) -> usize {
+    let points: Vec<Point> = coords
         .iter()
-        .map(|coord| Coord {
-            x: coord.x + max_distance,
-            y: coord.y + max_distance,
+        .map(|coord| Point {
+            x: coord.x as i64,
+            y: coord.y as i64,
         })
         .collect();
-    let mut stack: Vec<Coord> = Vec::with_capacity(1024);
-    stack.extend_from_slice(&coords);
-    let mut visited: HashSet<Coord> = HashSet::with_capacity(1024);
-
-    while let Some(coord) = stack.pop() {
-        if !visited.contains(&coord) {
-            let total_distance: usize = coords.iter().map(|c| manhattan_distance(c, &coord)).sum();
-            if total_distance < max_distance {
-                stack.push(Coord {
-                    x: coord.x - 1,
-                    y: coord.y,
-                });
-                stack.push(Coord {
-                    x: coord.x + 1,
-                    y: coord.y,
-                });
-                stack.push(Coord {
-                    x: coord.x,
-                    y: coord.y - 1,
-                });
-                stack.push(Coord {
-                    x: coord.x,
-                    y: coord.y + 1,
-                });
-                visited.insert(coord);
-            }
-        }
-    }
-    return visited.len();
-}
-
-fn manhattan_distance(p1: &Coord, p2: &Coord) -> usize {
-    let dist_x = if p1.x > p2.x {
-        p1.x - p2.x
-    } else {
-        p2.x - p1.x
-    };
-    let dist_y = if p1.y > p2.y {
-        p1.y - p2.y
-    } else {
-        p2.y - p1.y
-    };
-    return dist_x + dist_y;
+    safe_region_size(&points, max_distance as i64)
 }
 
 fn calc_area_sizes(world: &World, n_areas: usize) -> Vec<u32> {
@@ -262,22 +281,6 @@ struct Coord {
 mod test {
     use super::*;
 
-    #[test]
-    fn manhattan_distance_works_correctly() {
-        assert_eq!(
-            manhattan_distance(&Coord { x: 0, y: 1 }, &Coord { x: 0, y: 1 }),
-            0
-        );
-        assert_eq!(
-            manhattan_distance(&Coord { x: 10, y: 100 }, &Coord { x: 20, y: 200 }),
-            110
-        );
-        assert_eq!(
-            manhattan_distance(&Coord { x: 20, y: 200 }, &Coord { x: 10, y: 100 }),
-            110
-        );
-    }
-
     #[test]
     fn check_example_puzzle_2() {
         // given
@@ -325,6 +328,41 @@ mod test {
         assert_eq!(area_sizes[4], 17);
     }
 
+    #[test]
+    fn regions_agree_with_world_for_manhattan_metric() {
+        // given
+        let coords = [
+            Coord { x: 1, y: 1 },
+            Coord { x: 1, y: 6 },
+            Coord { x: 8, y: 3 },
+            Coord { x: 3, y: 4 },
+            Coord { x: 5, y: 5 },
+            Coord { x: 8, y: 9 },
+        ];
+        let points: Vec<Point> = coords
+            .iter()
+            .map(|c| Point {
+                x: c.x as i64,
+                y: c.y as i64,
+            })
+            .collect();
+
+        // when
+        let world = calc_areas(&coords);
+        let world_infinite = world.get_areas_at_border();
+        let world_sizes = calc_area_sizes(&world, coords.len());
+        let regions = Regions::compute(&points, Metric::Manhattan);
+        let regions_sizes = regions.area_sizes();
+
+        // then
+        assert_eq!(world_infinite, regions.areas_at_border());
+        for i in 0..coords.len() {
+            if !world_infinite.contains(&i) {
+                assert_eq!(world_sizes[i] as usize, regions_sizes[i]);
+            }
+        }
+    }
+
     #[test]
     fn calc_area_sizes_returns_correct_area_sizes() {
         // given
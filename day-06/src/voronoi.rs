@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    Euclidean,
+}
+
+impl Metric {
+    // for the euclidean metric, this is the squared distance: we only ever compare distances,
+    // and this way we can stay with exact integers
+    pub fn distance(self, p1: Point, p2: Point) -> i64 {
+        let dx = (p1.x - p2.x).abs();
+        let dy = (p1.y - p2.y).abs();
+        match self {
+            Metric::Manhattan => dx + dy,
+            Metric::Chebyshev => dx.max(dy),
+            Metric::Euclidean => dx * dx + dy * dy,
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Metric> {
+        match name {
+            "manhattan" => Some(Metric::Manhattan),
+            "chebyshev" => Some(Metric::Chebyshev),
+            "euclidean" => Some(Metric::Euclidean),
+            _ => None,
+        }
+    }
+}
+
+// the area each point owns within the bounding box of all points.
+// cells with more than one closest point belong to nobody
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Regions {
+    pub min: Point,
+    pub max: Point,
+    cells: Vec<Option<usize>>,
+    n_points: usize,
+}
+
+impl Regions {
+    pub fn compute(points: &[Point], metric: Metric) -> Regions {
+        let min = Point {
+            x: points.iter().map(|p| p.x).min().unwrap_or(0),
+            y: points.iter().map(|p| p.y).min().unwrap_or(0),
+        };
+        let max = Point {
+            x: points.iter().map(|p| p.x).max().unwrap_or(0),
+            y: points.iter().map(|p| p.y).max().unwrap_or(0),
+        };
+        let mut cells = Vec::with_capacity(((max.x - min.x + 1) * (max.y - min.y + 1)) as usize);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                cells.push(closest_point(points, Point { x, y }, metric));
+            }
+        }
+        Regions {
+            min,
+            max,
+            cells,
+            n_points: points.len(),
+        }
+    }
+
+    fn width(&self) -> i64 {
+        self.max.x - self.min.x + 1
+    }
+
+    pub fn owner(&self, p: Point) -> Option<usize> {
+        if p.x < self.min.x || p.x > self.max.x || p.y < self.min.y || p.y > self.max.y {
+            return None;
+        }
+        let index = (p.x - self.min.x) + (p.y - self.min.y) * self.width();
+        self.cells.get(index as usize).copied().flatten()
+    }
+
+    // number of cells within the bounding box that belong to each point
+    pub fn area_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.n_points];
+        for index in self.cells.iter().flatten() {
+            sizes[*index] += 1;
+        }
+        sizes
+    }
+
+    // areas that reach the border of the bounding box are considered to be infinite
+    pub fn areas_at_border(&self) -> HashSet<usize> {
+        let mut result = HashSet::new();
+        for x in self.min.x..=self.max.x {
+            result.extend(self.owner(Point { x, y: self.min.y }));
+            result.extend(self.owner(Point { x, y: self.max.y }));
+        }
+        for y in self.min.y..=self.max.y {
+            result.extend(self.owner(Point { x: self.min.x, y }));
+            result.extend(self.owner(Point { x: self.max.x, y }));
+        }
+        result
+    }
+
+    // the largest area that does not reach the border, as (index, size)
+    pub fn largest_finite_area(&self) -> Option<(usize, usize)> {
+        let infinite = self.areas_at_border();
+        self.area_sizes()
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !infinite.contains(index))
+            .max_by_key(|(_, size)| *size)
+    }
+}
+
+// index of the only point closest to p, None if there is a tie
+pub fn closest_point(points: &[Point], p: Point, metric: Metric) -> Option<usize> {
+    match closest_points(points, p, metric)[..] {
+        [index] => Some(index),
+        _ => None,
+    }
+}
+
+// indices of all points that are closest to p
+pub fn closest_points(points: &[Point], p: Point, metric: Metric) -> Vec<usize> {
+    let mut closest: Vec<usize> = Vec::with_capacity(2);
+    let mut best_distance = i64::MAX;
+    for (index, point) in points.iter().enumerate() {
+        let distance = metric.distance(*point, p);
+        if distance < best_distance {
+            best_distance = distance;
+            closest.clear();
+        }
+        if distance == best_distance {
+            closest.push(index);
+        }
+    }
+    closest
+}
+
+pub fn parse_points(lines: &[&str]) -> Vec<Point> {
+    lines.iter().filter_map(|line| parse_point(line)).collect()
+}
+
+fn parse_point(line: &str) -> Option<Point> {
+    let (x, y) = line.split_once(", ")?;
+    Some(Point {
+        x: x.parse().ok()?,
+        y: y.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example_points() -> Vec<Point> {
+        [(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)]
+            .iter()
+            .map(|(x, y)| Point { x: *x, y: *y })
+            .collect()
+    }
+
+    #[test]
+    fn metric_distance_works_correctly() {
+        let p1 = Point { x: -3, y: 2 };
+        let p2 = Point { x: 1, y: -1 };
+        assert_eq!(Metric::Manhattan.distance(p1, p2), 7);
+        assert_eq!(Metric::Chebyshev.distance(p1, p2), 4);
+        assert_eq!(Metric::Euclidean.distance(p1, p2), 25);
+    }
+
+    #[test]
+    fn regions_work_for_example_and_shifted_example() {
+        // given
+        let points = example_points();
+        let shifted: Vec<Point> = points
+            .iter()
+            .map(|p| Point {
+                x: p.x - 100,
+                y: p.y - 7,
+            })
+            .collect();
+
+        // when
+        let regions = Regions::compute(&points, Metric::Manhattan);
+        let shifted_regions = Regions::compute(&shifted, Metric::Manhattan);
+
+        // then
+        assert_eq!(regions.largest_finite_area(), Some((4, 17)));
+        assert_eq!(shifted_regions.largest_finite_area(), Some((4, 17)));
+        assert_eq!(regions.areas_at_border(), HashSet::from([0, 1, 2, 5]));
+        assert_eq!(regions.area_sizes()[3], 9);
+        assert_eq!(shifted_regions.owner(Point { x: -95, y: -2 }), Some(4));
+    }
+
+    #[test]
+    fn parse_points_accepts_negative_coordinates() {
+        assert_eq!(
+            parse_points(&["-1, 2", "foo", "3, -40"]),
+            vec![Point { x: -1, y: 2 }, Point { x: 3, y: -40 }]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+// nodes are stored sorted by name, so comparing node indices is the same as comparing names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dag {
+    names: Vec<String>,
+    dependencies: Vec<Vec<usize>>,
+    dependants: Vec<Vec<usize>>,
+}
+
+impl Dag {
+    // every edge is (dependency, dependant)
+    pub fn from_edges<S: AsRef<str>>(edges: &[(S, S)]) -> Dag {
+        let mut names: Vec<String> = edges
+            .iter()
+            .flat_map(|(dependency, dependant)| [dependency.as_ref(), dependant.as_ref()])
+            .map(|name| name.to_owned())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        let indices: HashMap<&str, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.as_str(), index))
+            .collect();
+
+        let mut dependencies: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+        let mut dependants: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+        for (dependency, dependant) in edges {
+            let dependency = indices[dependency.as_ref()];
+            let dependant = indices[dependant.as_ref()];
+            if !dependencies[dependant].contains(&dependency) {
+                dependencies[dependant].push(dependency);
+                dependants[dependency].push(dependant);
+            }
+        }
+        for list in dependencies.iter_mut().chain(dependants.iter_mut()) {
+            list.sort_unstable();
+        }
+        Dag {
+            names,
+            dependencies,
+            dependants,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn name(&self, node: usize) -> &str {
+        &self.names[node]
+    }
+
+    pub fn dependencies(&self, node: usize) -> &[usize] {
+        &self.dependencies[node]
+    }
+
+    pub fn dependants(&self, node: usize) -> &[usize] {
+        &self.dependants[node]
+    }
+
+    // all nodes in an order that respects the dependencies, if there are multiple nodes
+    // available, the alphabetically first one comes first
+    pub fn topological_order(&self) -> Result<Vec<usize>, String> {
+        let mut missing_dependencies: Vec<usize> =
+            self.dependencies.iter().map(|deps| deps.len()).collect();
+        let mut available: BinaryHeap<Reverse<usize>> = missing_dependencies
+            .iter()
+            .enumerate()
+            .filter(|(_, missing)| **missing == 0)
+            .map(|(node, _)| Reverse(node))
+            .collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(Reverse(node)) = available.pop() {
+            order.push(node);
+            for dependant in &self.dependants[node] {
+                missing_dependencies[*dependant] -= 1;
+                if missing_dependencies[*dependant] == 0 {
+                    available.push(Reverse(*dependant));
+                }
+            }
+        }
+        if order.len() < self.len() {
+            return Err(self.cycle_error());
+        }
+        Ok(order)
+    }
+
+    // a cycle of nodes, where each node depends on the next one (and the last on the first)
+    pub fn find_cycle(&self) -> Option<Vec<usize>> {
+        // 0: not visited, 1: on the current path, 2: done
+        let mut state = vec![0u8; self.len()];
+        for start in 0..self.len() {
+            if state[start] != 0 {
+                continue;
+            }
+            // explicit stack of (node, index of the next dependency to visit)
+            let mut path: Vec<(usize, usize)> = vec![(start, 0)];
+            state[start] = 1;
+            while let Some((node, next)) = path.last_mut() {
+                if let Some(dependency) = self.dependencies[*node].get(*next).copied() {
+                    *next += 1;
+                    match state[dependency] {
+                        0 => {
+                            state[dependency] = 1;
+                            path.push((dependency, 0));
+                        }
+                        1 => {
+                            let cycle_start = path
+                                .iter()
+                                .position(|(n, _)| *n == dependency)
+                                .expect("node on the current path");
+                            return Some(path[cycle_start..].iter().map(|(n, _)| *n).collect());
+                        }
+                        _ => (),
+                    }
+                } else {
+                    state[*node] = 2;
+                    path.pop();
+                }
+            }
+        }
+        None
+    }
+
+    pub fn cycle_error(&self) -> String {
+        match self.find_cycle() {
+            Some(cycle) => {
+                let mut names: Vec<&str> = cycle.iter().map(|node| self.name(*node)).collect();
+                names.push(self.name(cycle[0]));
+                format!(
+                    "The graph contains a cycle ({} nodes, each depends on the next): {}",
+                    cycle.len(),
+                    names.join(" -> ")
+                )
+            }
+            None => "The graph contains a cycle".to_owned(),
+        }
+    }
+}
+
+// the example from the puzzle description, shared by the tests of all modules
+#[cfg(test)]
+pub fn example_dag() -> Dag {
+    Dag::from_edges(&[
+        ("C", "A"),
+        ("C", "F"),
+        ("A", "B"),
+        ("A", "D"),
+        ("B", "E"),
+        ("D", "E"),
+        ("F", "E"),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn topological_order_uses_alphabetical_order_for_ties() {
+        // given
+        let dag = Dag::from_edges(&[("Zeta", "Alpha"), ("Beta", "Alpha"), ("Zeta", "Beta")]);
+
+        // when
+        let order = dag.topological_order().unwrap();
+
+        // then
+        let names: Vec<&str> = order.iter().map(|node| dag.name(*node)).collect();
+        assert_eq!(names, vec!["Zeta", "Beta", "Alpha"]);
+    }
+
+    #[test]
+    fn topological_order_reports_cycle() {
+        // given
+        let dag = Dag::from_edges(&[("A", "B"), ("B", "CC"), ("CC", "D"), ("D", "B"), ("A", "E")]);
+
+        // when
+        let cycle = dag.find_cycle().unwrap();
+        let error = dag.topological_order().unwrap_err();
+
+        // then
+        let mut names: Vec<&str> = cycle.iter().map(|node| dag.name(*node)).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["B", "CC", "D"]);
+        assert!(error.contains("B -> D -> CC -> B"), "{}", error);
+    }
+}
@@ -0,0 +1,141 @@
+use super::dag::Dag;
+use super::schedule::Schedule;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+// optional information to show in the graph
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DotAnnotations<'a> {
+    // start and end time of each step
+    pub schedule: Option<&'a Schedule>,
+    // nodes and edges on this path are drawn in red
+    pub critical_path: Option<&'a [usize]>,
+    // position of each node in this order, e.g. the alphabetical tie-break order
+    pub order: Option<&'a [usize]>,
+}
+
+// render the graph in the graphviz DOT format, edges point from a dependency to its dependant
+pub fn to_dot(dag: &Dag, annotations: &DotAnnotations) -> String {
+    let critical_nodes: HashSet<usize> = annotations
+        .critical_path
+        .map(|path| path.iter().copied().collect())
+        .unwrap_or_default();
+    let critical_edges: HashSet<(usize, usize)> = annotations
+        .critical_path
+        .map(|path| path.windows(2).map(|w| (w[0], w[1])).collect())
+        .unwrap_or_default();
+
+    let mut dot = String::from("digraph steps {\n    rankdir=LR;\n");
+    for node in 0..dag.len() {
+        let mut label = escape(dag.name(node));
+        if let Some(position) = annotations
+            .order
+            .and_then(|order| order.iter().position(|n| *n == node))
+        {
+            write!(label, "\\n#{}", position + 1).expect("writing to a string does not fail");
+        }
+        if let Some(task) = annotations
+            .schedule
+            .and_then(|schedule| schedule.tasks.iter().find(|task| task.node == node))
+        {
+            write!(
+                label,
+                "\\n{}-{} s (worker {})",
+                task.start, task.end, task.worker
+            )
+            .expect("writing to a string does not fail");
+        }
+        let style = if critical_nodes.contains(&node) {
+            ", color=red, penwidth=2"
+        } else {
+            ""
+        };
+        writeln!(
+            dot,
+            "    \"{}\" [label=\"{}\"{}];",
+            escape(dag.name(node)),
+            label,
+            style
+        )
+        .expect("writing to a string does not fail");
+    }
+    for node in 0..dag.len() {
+        for dependant in dag.dependants(node) {
+            let style = if critical_edges.contains(&(node, *dependant)) {
+                " [color=red, penwidth=2]"
+            } else {
+                ""
+            };
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\"{};",
+                escape(dag.name(node)),
+                escape(dag.name(*dependant)),
+                style
+            )
+            .expect("writing to a string does not fail");
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::dag::example_dag;
+    use super::super::schedule::{schedule, Worker};
+    use super::*;
+
+    #[test]
+    fn to_dot_renders_plain_graph() {
+        // given
+        let dag = Dag::from_edges(&[("C", "A"), ("C", "F\"oo")]);
+
+        // when
+        let dot = to_dot(&dag, &DotAnnotations::default());
+
+        // then
+        assert_eq!(
+            dot,
+            "digraph steps {
+    rankdir=LR;
+    \"A\" [label=\"A\"];
+    \"C\" [label=\"C\"];
+    \"F\\\"oo\" [label=\"F\\\"oo\"];
+    \"C\" -> \"A\";
+    \"C\" -> \"F\\\"oo\";
+}
+"
+        );
+    }
+
+    #[test]
+    fn to_dot_shows_annotations() {
+        // given
+        let dag = example_dag();
+        let order = dag.topological_order().unwrap();
+        let schedule = schedule(&dag, &vec![Worker::default(); 2], &[1, 2, 3, 4, 5, 6]).unwrap();
+        // C, F, E
+        let critical_path = [2, 5, 4];
+
+        // when
+        let dot = to_dot(
+            &dag,
+            &DotAnnotations {
+                schedule: Some(&schedule),
+                critical_path: Some(&critical_path),
+                order: Some(&order),
+            },
+        );
+
+        // then
+        assert!(dot.contains("\"A\" [label=\"A\\n#2\\n3-4 s (worker 0)\"];"));
+        assert!(dot.contains("\"F\" [label=\"F\\n#5\\n3-9 s (worker 1)\", color=red, penwidth=2];"));
+        assert!(dot.contains("\"C\" -> \"F\" [color=red, penwidth=2];"));
+        assert!(dot.contains("\"C\" -> \"A\";"));
+    }
+}
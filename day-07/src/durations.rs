@@ -0,0 +1,70 @@
+use super::dag::Dag;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Durations {
+    // base seconds plus the position in the alphabet of each letter of the step
+    Formula { base: u32 },
+    Table(HashMap<String, u32>),
+}
+
+impl Durations {
+    pub fn duration(&self, step: &str) -> Option<u32> {
+        match self {
+            Durations::Formula { base } => step
+                .chars()
+                .map(|c| {
+                    c.is_ascii_alphabetic()
+                        .then(|| c.to_ascii_uppercase() as u32 - 'A' as u32 + 1)
+                })
+                .sum::<Option<u32>>()
+                .map(|letters| base + letters),
+            Durations::Table(table) => table.get(step).copied(),
+        }
+    }
+
+    // the duration for each node of the DAG, indexed by node
+    pub fn for_dag(&self, dag: &Dag) -> Result<Vec<u32>, String> {
+        (0..dag.len())
+            .map(|node| {
+                self.duration(dag.name(node))
+                    .ok_or_else(|| format!("No duration for step {}", dag.name(node)))
+            })
+            .collect()
+    }
+}
+
+// one step per line: the name of the step and its duration, e.g. "A 61"
+pub fn parse_durations(input: &str) -> Result<Durations, String> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next().map(|d| d.parse()), parts.next()) {
+                (Some(step), Some(Ok(duration)), None) => Ok((step.to_owned(), duration)),
+                _ => Err(format!("Invalid duration line '{line}'")),
+            }
+        })
+        .collect::<Result<HashMap<String, u32>, String>>()
+        .map(Durations::Table)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn durations_work_for_formula_and_table() {
+        let formula = Durations::Formula { base: 60 };
+        assert_eq!(formula.duration("A"), Some(61));
+        assert_eq!(formula.duration("Z"), Some(86));
+        assert_eq!(formula.duration("AB"), Some(63));
+        assert_eq!(formula.duration("A1"), None);
+
+        let table = parse_durations("A 5\nFoo 17\n").unwrap();
+        assert_eq!(table.duration("Foo"), Some(17));
+        assert_eq!(table.duration("B"), None);
+        assert!(parse_durations("A five\n").is_err());
+    }
+}
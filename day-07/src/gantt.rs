@@ -0,0 +1,130 @@
+use super::dag::Dag;
+use super::schedule::Schedule;
+use std::fmt::Write;
+
+// one row per worker, each cell stands for `scale` seconds and shows the name of the step the
+// worker is busy with at the beginning of that time span ('.' if idle). All cells are as wide as
+// the longest step name, so steps that only share a prefix can still be told apart.
+pub fn gantt_chart(dag: &Dag, schedule: &Schedule, n_workers: usize, max_width: u32) -> String {
+    let cell_width = (0..dag.len())
+        .map(|node| dag.name(node).chars().count())
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    let max_columns = (max_width as usize / cell_width).max(1) as u32;
+    let scale = schedule.total_time.div_ceil(max_columns).max(1);
+    let columns = schedule.total_time.div_ceil(scale);
+    let label_width = format!("Worker {}", n_workers.saturating_sub(1)).len();
+
+    let mut chart = String::new();
+    writeln!(
+        chart,
+        "{:label_width$} 0 s to {} s, one cell is {} s",
+        "", schedule.total_time, scale
+    )
+    .expect("writing to a string does not fail");
+    for worker in 0..n_workers {
+        let row: String = (0..columns)
+            .map(|column| {
+                let time = column * scale;
+                schedule
+                    .tasks
+                    .iter()
+                    .find(|task| task.worker == worker && task.start <= time && time < task.end)
+                    .map(|task| format!("{:cell_width$}", dag.name(task.node)))
+                    .unwrap_or_else(|| ".".repeat(cell_width))
+            })
+            .collect();
+        writeln!(
+            chart,
+            "{:label_width$} |{}|",
+            format!("Worker {worker}"),
+            row
+        )
+        .expect("writing to a string does not fail");
+    }
+    chart
+}
+
+pub fn schedule_csv(dag: &Dag, schedule: &Schedule) -> String {
+    let mut tasks = schedule.tasks.clone();
+    tasks.sort_by_key(|task| (task.worker, task.start));
+    let mut csv = String::from("worker,step,start,end\n");
+    for task in tasks {
+        writeln!(
+            csv,
+            "{},{},{},{}",
+            task.worker,
+            dag.name(task.node),
+            task.start,
+            task.end
+        )
+        .expect("writing to a string does not fail");
+    }
+    csv
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::dag::example_dag;
+    use super::super::schedule::{schedule, Worker};
+    use super::*;
+
+    fn example() -> (Dag, Schedule) {
+        let dag = example_dag();
+        let schedule = schedule(&dag, &vec![Worker::default(); 2], &[1, 2, 3, 4, 5, 6]).unwrap();
+        (dag, schedule)
+    }
+
+    #[test]
+    fn gantt_chart_matches_example() {
+        // given
+        let (dag, schedule) = example();
+
+        // when
+        let chart = gantt_chart(&dag, &schedule, 2, 80);
+
+        // then
+        assert_eq!(
+            chart,
+            "         0 s to 15 s, one cell is 1 s
+Worker 0 |CCCABBDDDDEEEEE|
+Worker 1 |...FFFFFF......|
+"
+        );
+    }
+
+    #[test]
+    fn gantt_chart_pads_multi_character_names() {
+        // given
+        let dag = Dag::from_edges(&[("Aa", "Ab"), ("Aa", "B")]);
+        let schedule = schedule(&dag, &vec![Worker::default(); 2], &[1, 2, 3]).unwrap();
+
+        // when
+        let chart = gantt_chart(&dag, &schedule, 2, 80);
+
+        // then
+        assert_eq!(
+            chart,
+            "         0 s to 4 s, one cell is 1 s
+Worker 0 |AaAbAb..|
+Worker 1 |..B B B |
+"
+        );
+    }
+
+    #[test]
+    fn schedule_csv_lists_tasks_per_worker() {
+        // given
+        let (dag, schedule) = example();
+
+        // when
+        let csv = schedule_csv(&dag, &schedule);
+
+        // then
+        assert_eq!(
+            csv,
+            "worker,step,start,end\n0,C,0,3\n0,A,3,4\n0,B,4,6\n0,D,6,10\n0,E,10,15\n1,F,3,9\n"
+        );
+    }
+}
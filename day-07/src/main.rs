@@ -1,4 +1,14 @@
-use std::collections::HashMap;
+mod dag;
+mod dot;
+mod durations;
+mod gantt;
+mod schedule;
+
+use self::dag::Dag;
+use self::dot::{to_dot, DotAnnotations};
+use self::durations::{parse_durations, Durations};
+use self::gantt::{gantt_chart, schedule_csv};
+use self::schedule::{critical_path, parse_workers, schedule, Worker};
 use std::env;
 use std::fs::read_to_string;
 use std::path::Path;
@@ -8,115 +18,108 @@ fn main() -> Result<(), String> {
     let content = read_to_string(Path::new(&filename)).map_err(|e| e.to_string())?;
     let lines: Vec<&str> = content.split('\n').collect();
 
-    let inverse_dag = parse_inverse_dag(&lines);
-    let opt_ordered_nodes = work_on_nodes(&inverse_dag, 1);
-
-    if let Some(ordered_nodes) = opt_ordered_nodes {
-        println!(
-            "The correct order is: {}",
-            ordered_nodes.0.iter().collect::<String>()
-        );
-    } else {
-        println!("Apparently, the graph is not a fully connected DAG.");
-    }
-
-    let opt_parallel_result = work_on_nodes(&inverse_dag, 5);
-
-    if let Some(parallel_result) = opt_parallel_result {
-        println!("With four helping elves, it takes {} seconds. The steps have been finished in order {}", parallel_result.1, parallel_result.0.iter().collect::<String>());
-    } else {
-        println!("With four helping elves, everything ended in chaos.");
-    }
-
-    Ok(())
-}
-
-fn time(node: char) -> u32 {
-    61 + (node.to_ascii_uppercase() as u32 - 'A' as u32)
-}
-
-fn work_on_nodes(inv_dag: &HashMap<char, Vec<char>>, n_workers: usize) -> Option<(Vec<char>, u32)> {
-    let mut result: Vec<char> = Vec::with_capacity(inv_dag.len());
-    let mut workers: Vec<Option<(char, u32)>> = (0..n_workers).map(|_| None).collect();
-    let mut used_time: u32 = 0;
-
-    while result.len() < inv_dag.len() {
-        // give available jobs to free workers
-        for i in 0..workers.len() {
-            if workers[i].is_none() {
-                if let Some(min_independent_node) = get_next_node(inv_dag, &result, &workers) {
-                    workers[i] = Some((min_independent_node, time(min_independent_node)));
-                } else {
-                    break;
+    // defaults are the puzzle settings: you and four helping elves, 60 seconds + letter
+    let mut workers = vec![Worker::default(); 5];
+    let mut durations = Durations::Formula { base: 60 };
+    let mut output: Option<String> = None;
+    for arg in env::args().skip(2) {
+        let (key, value) = arg
+            .split_once('=')
+            .ok_or_else(|| format!("Expected option=value, got '{arg}'"))?;
+        match key {
+            "workers" => {
+                workers = match value.parse::<usize>() {
+                    Ok(n) => vec![Worker::default(); n],
+                    Err(_) => parse_workers(&read_file(value)?)?,
                 }
             }
+            "durations" => durations = parse_durations(&read_file(value)?)?,
+            "base" => {
+                durations = Durations::Formula {
+                    base: value
+                        .parse()
+                        .map_err(|_| format!("Invalid base: {value}"))?,
+                }
+            }
+            "output" => output = Some(value.to_owned()),
+            _ => return Err(format!("Unknown option: {key}")),
         }
-        // minimum time to wait until at least one worker finishes
-        let wait_time = workers.iter().filter_map(|w| w.map(|(_, t)| t)).min()?;
+    }
 
-        // update required time
-        workers = workers
+    let dag = Dag::from_edges(&parse_edges(&lines));
+    let ordered_nodes = dag.topological_order()?;
+    println!(
+        "The correct order is: {}",
+        ordered_nodes
             .iter()
-            .map(|worker| worker.map(|(node, t)| (node, t - wait_time)))
-            .collect();
-        used_time += wait_time;
-
-        // mark finished jobs
-        for (node, _) in workers.iter().filter_map(|w| *w).filter(|(_, t)| *t == 0) {
-            result.push(node);
-        }
-        // remove finished jobs from workers
-        workers = workers
+            .map(|node| dag.name(*node))
+            .collect::<String>()
+    );
+
+    let durations = durations.for_dag(&dag)?;
+    let parallel_result = schedule(&dag, &workers, &durations)?;
+    println!(
+        "With {} workers, it takes {} seconds. The steps have been finished in order {}",
+        workers.len(),
+        parallel_result.total_time,
+        parallel_result
+            .order()
             .iter()
-            .map(|w| w.and_then(|(node, time)| if time == 0 { None } else { Some((node, time)) }))
-            .collect();
+            .map(|node| dag.name(*node))
+            .collect::<String>()
+    );
+
+    let (path, length) = critical_path(&dag, &durations)?;
+    println!(
+        "The critical path takes {} seconds: {}",
+        length,
+        path.iter()
+            .map(|node| dag.name(*node))
+            .collect::<Vec<&str>>()
+            .join(" -> ")
+    );
+
+    match output.as_deref() {
+        Some("gantt") => print!(
+            "{}",
+            gantt_chart(&dag, &parallel_result, workers.len(), 100)
+        ),
+        Some("csv") => print!("{}", schedule_csv(&dag, &parallel_result)),
+        Some("dot") => print!(
+            "{}",
+            to_dot(
+                &dag,
+                &DotAnnotations {
+                    schedule: Some(&parallel_result),
+                    critical_path: Some(&path),
+                    order: Some(&ordered_nodes),
+                }
+            )
+        ),
+        Some(other) => return Err(format!("Unknown output format: {other}")),
+        None => (),
     }
 
-    Some((result, used_time))
+    Ok(())
 }
 
-fn get_next_node(
-    inv_dag: &HashMap<char, Vec<char>>,
-    finished_jobs: &[char],
-    workers: &[Option<(char, u32)>],
-) -> Option<char> {
-    inv_dag
-        .iter()
-        .filter(|(dependant, dependencies)| {
-            !finished_jobs.contains(dependant)
-                && !workers
-                    .iter()
-                    .filter_map(|w| *w)
-                    .any(|(node, _)| node == **dependant)
-                && dependencies.iter().all(|dep| finished_jobs.contains(dep))
-        })
-        .min_by_key(|(dependant, _)| *dependant)
-        .map(|(d, _)| *d)
+fn read_file(name: &str) -> Result<String, String> {
+    read_to_string(Path::new(name)).map_err(|e| format!("Cannot read {name}: {e}"))
 }
 
-fn parse_inverse_dag(lines: &[&str]) -> HashMap<char, Vec<char>> {
-    lines.iter().filter_map(|line| parse_line(line)).fold(
-        HashMap::with_capacity(lines.len()),
-        |mut edges, (dependency, dependant)| {
-            edges
-                .entry(dependant)
-                .or_insert_with(|| Vec::with_capacity(10))
-                .push(dependency);
-            // just so we have all edges in the map, we also insert the dependency node
-            edges
-                .entry(dependency)
-                .or_insert_with(|| Vec::with_capacity(10));
-            edges
-        },
-    )
+fn parse_edges(lines: &[&str]) -> Vec<(String, String)> {
+    lines.iter().filter_map(|line| parse_line(line)).collect()
 }
 
-fn parse_line(line: &str) -> Option<(char, char)> {
+fn parse_line(line: &str) -> Option<(String, String)> {
     let (dependency, dependant) = line.split_once(" must be finished before step ")?;
-    let dependency = dependency.strip_prefix("Step ")?.chars().next()?;
-    let dependant = dependant.strip_suffix(" can begin.")?.chars().next()?;
+    let dependency = dependency.strip_prefix("Step ")?;
+    let dependant = dependant.strip_suffix(" can begin.")?;
+    if dependency.is_empty() || dependant.is_empty() {
+        return None;
+    }
 
-    Some((dependency, dependant))
+    Some((dependency.to_owned(), dependant.to_owned()))
 }
 
 #[cfg(test)]
@@ -133,31 +136,42 @@ mod test {
         "Step F must be finished before step E can begin.",
     ];
 
+    fn names(dag: &Dag, nodes: &[usize]) -> Vec<String> {
+        nodes
+            .iter()
+            .map(|node| dag.name(*node).to_owned())
+            .collect()
+    }
+
     #[test]
-    fn work_on_nodes_works_for_example() {
+    fn topological_order_works_for_example() {
         // given
-        let dag = parse_inverse_dag(&EXAMPLE_LINES);
+        let dag = Dag::from_edges(&parse_edges(&EXAMPLE_LINES));
 
         // when
-        let result = work_on_nodes(&dag, 1).expect("expected a result");
+        let result = dag.topological_order().expect("expected a result");
 
         // then
-        assert_eq!(result.0, vec!['C', 'A', 'B', 'D', 'F', 'E'])
+        assert_eq!(names(&dag, &result), vec!["C", "A", "B", "D", "F", "E"])
     }
 
     #[test]
-    fn parse_inverse_dags_works_correctly() {
+    fn parse_edges_builds_correct_dag() {
         // when
-        let dag = parse_inverse_dag(&EXAMPLE_LINES);
+        let dag = Dag::from_edges(&parse_edges(&EXAMPLE_LINES));
 
         // then
+        let node = |name| (0..dag.len()).find(|n| dag.name(*n) == name).unwrap();
         assert_eq!(dag.len(), 6);
-        assert_eq!(dag.get(&'C'), Some(&vec![]));
-        assert_eq!(dag.get(&'A'), Some(&vec!['C']));
-        assert_eq!(dag.get(&'F'), Some(&vec!['C']));
-        assert_eq!(dag.get(&'B'), Some(&vec!['A']));
-        assert_eq!(dag.get(&'D'), Some(&vec!['A']));
-        assert_eq!(dag.get(&'E'), Some(&vec!['B', 'D', 'F']));
+        assert!(dag.dependencies(node("C")).is_empty());
+        assert_eq!(names(&dag, dag.dependencies(node("A"))), vec!["C"]);
+        assert_eq!(names(&dag, dag.dependencies(node("F"))), vec!["C"]);
+        assert_eq!(names(&dag, dag.dependencies(node("B"))), vec!["A"]);
+        assert_eq!(names(&dag, dag.dependencies(node("D"))), vec!["A"]);
+        assert_eq!(
+            names(&dag, dag.dependencies(node("E"))),
+            vec!["B", "D", "F"]
+        );
     }
 
     #[test]
@@ -169,7 +183,11 @@ mod test {
         let (dependency, dependant) = parse_line(line).expect("Expected something");
 
         // then
-        assert_eq!(dependency, 'D');
-        assert_eq!(dependant, 'E');
+        assert_eq!(dependency, "D");
+        assert_eq!(dependant, "E");
+        assert_eq!(
+            parse_line("Step Foo must be finished before step Bar can begin."),
+            Some(("Foo".to_owned(), "Bar".to_owned()))
+        );
     }
 }
@@ -0,0 +1,260 @@
+use super::dag::Dag;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Task {
+    pub node: usize,
+    pub worker: usize,
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Schedule {
+    // in the order the tasks were finished
+    pub tasks: Vec<Task>,
+    pub total_time: u32,
+}
+
+impl Schedule {
+    pub fn order(&self) -> Vec<usize> {
+        self.tasks.iter().map(|task| task.node).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worker {
+    // 100 is normal speed, 50 takes twice as long
+    pub speed_percent: u32,
+    // None if the worker can do every step
+    pub steps: Option<HashSet<String>>,
+}
+
+impl Default for Worker {
+    fn default() -> Worker {
+        Worker {
+            speed_percent: 100,
+            steps: None,
+        }
+    }
+}
+
+impl Worker {
+    pub fn can_do(&self, step: &str) -> bool {
+        self.steps
+            .as_ref()
+            .map(|steps| steps.contains(step))
+            .unwrap_or(true)
+    }
+
+    pub fn time_for(&self, duration: u32) -> u32 {
+        (duration * 100).div_ceil(self.speed_percent)
+    }
+}
+
+// one worker per line: the speed in percent, optionally followed by a comma separated list
+// of the steps the worker can do, e.g. "50 A,B,C"
+pub fn parse_workers(input: &str) -> Result<Vec<Worker>, String> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            let speed_percent: u32 = parts
+                .next()
+                .and_then(|speed| speed.parse().ok())
+                .filter(|speed| *speed > 0)
+                .ok_or_else(|| format!("Invalid worker speed in line '{line}'"))?;
+            let steps = parts
+                .next()
+                .map(|steps| steps.split(',').map(|step| step.to_owned()).collect());
+            if parts.next().is_some() {
+                return Err(format!("Unexpected input in worker line '{line}'"));
+            }
+            Ok(Worker {
+                speed_percent,
+                steps,
+            })
+        })
+        .collect()
+}
+
+// work on all nodes with the given workers in parallel. Whenever a worker is free, it starts
+// working on the alphabetically first available node it can do. Workers with lower numbers
+// are served first. durations contains the base duration for each node.
+pub fn schedule(dag: &Dag, workers: &[Worker], durations: &[u32]) -> Result<Schedule, String> {
+    let mut missing_dependencies: Vec<usize> = (0..dag.len())
+        .map(|node| dag.dependencies(node).len())
+        .collect();
+    let mut available: BTreeSet<usize> = missing_dependencies
+        .iter()
+        .enumerate()
+        .filter(|(_, missing)| **missing == 0)
+        .map(|(node, _)| node)
+        .collect();
+    let mut free_workers: BTreeSet<usize> = (0..workers.len()).collect();
+    // ordered by end time, then by worker
+    let mut running: BinaryHeap<Reverse<(u32, usize, usize, u32)>> = BinaryHeap::new();
+    let mut tasks: Vec<Task> = Vec::with_capacity(dag.len());
+    let mut now: u32 = 0;
+
+    loop {
+        let mut busy: Vec<usize> = Vec::with_capacity(free_workers.len());
+        for worker in &free_workers {
+            let next = available
+                .iter()
+                .copied()
+                .find(|node| workers[*worker].can_do(dag.name(*node)));
+            if let Some(node) = next {
+                available.remove(&node);
+                busy.push(*worker);
+                let end = now + workers[*worker].time_for(durations[node]);
+                running.push(Reverse((end, *worker, node, now)));
+            }
+        }
+        for worker in busy {
+            free_workers.remove(&worker);
+        }
+
+        let Some(Reverse((end, _, _, _))) = running.peek().copied() else {
+            break;
+        };
+        now = end;
+        while let Some(Reverse((end, worker, node, start))) = running.peek().copied() {
+            if end != now {
+                break;
+            }
+            running.pop();
+            tasks.push(Task {
+                node,
+                worker,
+                start,
+                end,
+            });
+            free_workers.insert(worker);
+            for dependant in dag.dependants(node) {
+                missing_dependencies[*dependant] -= 1;
+                if missing_dependencies[*dependant] == 0 {
+                    available.insert(*dependant);
+                }
+            }
+        }
+    }
+
+    if tasks.len() < dag.len() {
+        if !available.is_empty() {
+            let names: Vec<&str> = available.iter().map(|node| dag.name(*node)).collect();
+            return Err(format!("No worker can do the steps {}", names.join(", ")));
+        }
+        return Err(dag.cycle_error());
+    }
+    Ok(Schedule {
+        tasks,
+        total_time: now,
+    })
+}
+
+// the chain of dependent steps with the longest total duration, and that duration.
+// No schedule can be faster than this, no matter how many workers there are.
+pub fn critical_path(dag: &Dag, durations: &[u32]) -> Result<(Vec<usize>, u32), String> {
+    let order = dag.topological_order()?;
+    // earliest possible end time of each node and the dependency that determines it
+    let mut earliest_end: Vec<u32> = vec![0; dag.len()];
+    let mut predecessor: Vec<Option<usize>> = vec![None; dag.len()];
+    for node in order {
+        let latest_dependency = dag
+            .dependencies(node)
+            .iter()
+            .copied()
+            .max_by_key(|dependency| (earliest_end[*dependency], Reverse(*dependency)));
+        predecessor[node] = latest_dependency;
+        earliest_end[node] =
+            latest_dependency.map(|d| earliest_end[d]).unwrap_or(0) + durations[node];
+    }
+    let Some(last) = (0..dag.len()).max_by_key(|node| (earliest_end[*node], Reverse(*node))) else {
+        return Ok((Vec::new(), 0));
+    };
+    let mut path = vec![last];
+    while let Some(previous) = predecessor[path[path.len() - 1]] {
+        path.push(previous);
+    }
+    path.reverse();
+    Ok((path, earliest_end[last]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::dag::example_dag;
+    use super::*;
+
+    // A=1, B=2, …
+    const EXAMPLE_DURATIONS: [u32; 6] = [1, 2, 3, 4, 5, 6];
+
+    #[test]
+    fn schedule_works_for_example() {
+        // given
+        let dag = example_dag();
+
+        // when
+        let result = schedule(&dag, &vec![Worker::default(); 2], &EXAMPLE_DURATIONS).unwrap();
+
+        // then
+        // nodes are numbered alphabetically, so B is 1
+        let order: String = result.order().iter().map(|node| dag.name(*node)).collect();
+        assert_eq!(order, "CABFDE");
+        assert_eq!(result.total_time, 15);
+        assert_eq!(
+            result.tasks[2],
+            Task {
+                node: 1,
+                worker: 0,
+                start: 4,
+                end: 6
+            }
+        );
+    }
+
+    #[test]
+    fn schedule_respects_worker_restrictions_and_speed() {
+        // given
+        let dag = example_dag();
+        let workers = parse_workers("50 C,E\n100 A,B,D,F\n").unwrap();
+
+        // when
+        let result = schedule(&dag, &workers, &EXAMPLE_DURATIONS).unwrap();
+
+        // then
+        // C takes 6 seconds for the slow worker, then the other worker does A, B, D and F
+        // in 1 + 2 + 4 + 6 seconds, then the slow worker does E in 10 seconds
+        assert_eq!(result.total_time, 29);
+        assert!(result
+            .tasks
+            .iter()
+            .all(|task| workers[task.worker].can_do(dag.name(task.node))));
+    }
+
+    #[test]
+    fn schedule_reports_unassignable_steps_and_cycles() {
+        let dag = Dag::from_edges(&[("A", "B"), ("B", "A")]);
+        assert!(schedule(&dag, &[Worker::default()], &[1, 1]).is_err());
+
+        let workers = parse_workers("100 A,B,C,D,F\n").unwrap();
+        let error = schedule(&example_dag(), &workers, &EXAMPLE_DURATIONS).unwrap_err();
+        assert_eq!(error, "No worker can do the steps E");
+    }
+
+    #[test]
+    fn critical_path_works_for_example() {
+        // given
+        let dag = example_dag();
+
+        // when
+        let (path, length) = critical_path(&dag, &EXAMPLE_DURATIONS).unwrap();
+
+        // then
+        let names: Vec<&str> = path.iter().map(|node| dag.name(*node)).collect();
+        assert_eq!(names, vec!["C", "F", "E"]);
+        assert_eq!(length, 14);
+    }
+}
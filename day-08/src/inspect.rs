@@ -0,0 +1,317 @@
+use super::Node;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fmt::Write;
+
+// the indices of the children to follow from the root, the root itself has an empty path
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NodePath(pub Vec<usize>);
+
+// e.g. "0/2/1", the root is "/"
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "/");
+        }
+        let parts: Vec<String> = self.0.iter().map(|i| i.to_string()).collect();
+        write!(f, "{}", parts.join("/"))
+    }
+}
+
+pub fn parse_path(path: &str) -> Result<NodePath, String> {
+    let path = path.trim().trim_matches('/');
+    if path.is_empty() {
+        return Ok(NodePath::default());
+    }
+    path.split('/')
+        .map(|part| {
+            part.parse::<usize>()
+                .map_err(|_| format!("Invalid child index '{}' in path '{}'", part, path))
+        })
+        .collect::<Result<Vec<usize>, String>>()
+        .map(NodePath)
+}
+
+pub fn node_at<'a>(tree: &'a Node, path: &NodePath) -> Option<&'a Node> {
+    let mut node = tree;
+    for index in &path.0 {
+        node = node.children.get(*index)?;
+    }
+    Some(node)
+}
+
+// The iterators yield each node with its depth, the root has depth 0. Copying the path for
+// every node would make a traversal quadratic in the depth, so the depth first iterator only
+// builds the path of the node it returned last when `path()` is called.
+
+// pre-order: every node comes before its children, children in their original order
+pub struct DepthFirst<'a> {
+    // depth, index among the siblings and node
+    stack: Vec<(usize, usize, &'a Node)>,
+    // the path of the node that was returned last. The next node's path is a prefix of it plus
+    // one more index, so it is updated in place.
+    path: Vec<usize>,
+}
+
+impl<'a> DepthFirst<'a> {
+    pub fn path(&self) -> NodePath {
+        NodePath(self.path.clone())
+    }
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = (usize, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, index, node) = self.stack.pop()?;
+        if depth == 0 {
+            self.path.clear();
+        } else {
+            self.path.truncate(depth - 1);
+            self.path.push(index);
+        }
+        for (index, child) in node.children.iter().enumerate().rev() {
+            self.stack.push((depth + 1, index, child));
+        }
+        Some((depth, node))
+    }
+}
+
+pub fn depth_first(tree: &Node) -> DepthFirst<'_> {
+    DepthFirst {
+        stack: vec![(0, 0, tree)],
+        path: Vec::new(),
+    }
+}
+
+// level by level, starting with the root
+pub struct BreadthFirst<'a> {
+    queue: VecDeque<(usize, &'a Node)>,
+}
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = (usize, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, node) = self.queue.pop_front()?;
+        for child in node.children.iter() {
+            self.queue.push_back((depth + 1, child));
+        }
+        Some((depth, node))
+    }
+}
+
+pub fn breadth_first(tree: &Node) -> BreadthFirst<'_> {
+    BreadthFirst {
+        queue: VecDeque::from(vec![(0, tree)]),
+    }
+}
+
+// a metadata entry of a node with children, and the child it refers to (if it exists)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Reference {
+    pub entry: usize,
+    pub child: Option<usize>,
+    pub value: usize,
+}
+
+// how the value of a node comes about. Leaves have no references, their value is the sum of
+// their metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Explanation {
+    pub value: usize,
+    pub references: Vec<Reference>,
+}
+
+// the explanation for every node, in depth first order
+pub fn explain_values(tree: &Node) -> Vec<Explanation> {
+    let nodes: Vec<&Node> = depth_first(tree).map(|(_, node)| node).collect();
+    // in depth first order, the first child of node i is i + 1, the next sibling of a node
+    // comes right after its subtree. Going backwards, all children are done before their parent.
+    let mut subtree_sizes: Vec<usize> = vec![0; nodes.len()];
+    let mut explanations: Vec<Option<Explanation>> = vec![None; nodes.len()];
+    for (i, node) in nodes.iter().enumerate().rev() {
+        let mut child_indices: Vec<usize> = Vec::with_capacity(node.children.len());
+        let mut next = i + 1;
+        for _ in &node.children {
+            child_indices.push(next);
+            next += subtree_sizes[next];
+        }
+        subtree_sizes[i] = next - i;
+
+        let explanation = if node.children.is_empty() {
+            Explanation {
+                value: node.metadata.iter().sum(),
+                references: Vec::new(),
+            }
+        } else {
+            let references: Vec<Reference> = node
+                .metadata
+                .iter()
+                .map(|entry| {
+                    let child = entry.checked_sub(1).filter(|c| *c < child_indices.len());
+                    let value = child
+                        .and_then(|c| explanations[child_indices[c]].as_ref())
+                        .map(|e| e.value)
+                        .unwrap_or(0);
+                    Reference {
+                        entry: *entry,
+                        child,
+                        value,
+                    }
+                })
+                .collect();
+            Explanation {
+                value: references.iter().map(|r| r.value).sum(),
+                references,
+            }
+        };
+        explanations[i] = Some(explanation);
+    }
+    explanations
+        .into_iter()
+        .map(|e| e.expect("all nodes are explained"))
+        .collect()
+}
+
+// one line per node, indented by depth, with its path, metadata and value. Paths are relative
+// to the given node.
+pub fn pretty_print(tree: &Node) -> String {
+    let mut output = String::new();
+    let mut nodes = depth_first(tree);
+    let mut explanations = explain_values(tree).into_iter();
+    while let Some((depth, node)) = nodes.next() {
+        let explanation = explanations.next().expect("all nodes are explained");
+        let metadata: Vec<String> = node.metadata.iter().map(|m| m.to_string()).collect();
+        write!(
+            output,
+            "{:indent$}{} [{}] value {}",
+            "",
+            nodes.path(),
+            metadata.join(", "),
+            explanation.value,
+            indent = 2 * depth
+        )
+        .expect("writing to a string does not fail");
+        if node.children.is_empty() {
+            output.push_str(" (leaf, sum of metadata)");
+        } else {
+            let references: Vec<String> = explanation
+                .references
+                .iter()
+                .map(|r| match r.child {
+                    Some(child) => format!("{} -> child {} = {}", r.entry, child, r.value),
+                    None => format!("{} -> no child = 0", r.entry),
+                })
+                .collect();
+            write!(output, ": {}", references.join(", "))
+                .expect("writing to a string does not fail");
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::read_tree;
+    use super::*;
+
+    const EXAMPLE_INPUT: [usize; 16] = [2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2];
+
+    fn example_tree() -> Node {
+        read_tree(&mut EXAMPLE_INPUT.iter().copied()).unwrap()
+    }
+
+    #[test]
+    fn iterators_visit_nodes_in_order() {
+        // given
+        let tree = example_tree();
+
+        // when
+        let mut nodes = depth_first(&tree);
+        let mut depth_first: Vec<(String, usize)> = Vec::new();
+        while let Some((depth, _)) = nodes.next() {
+            depth_first.push((nodes.path().to_string(), depth));
+        }
+        let breadth_first: Vec<(usize, usize)> = breadth_first(&tree)
+            .map(|(depth, node)| (depth, node.metadata[0]))
+            .collect();
+
+        // then
+        assert_eq!(
+            depth_first,
+            vec![
+                ("/".to_owned(), 0),
+                ("0".to_owned(), 1),
+                ("1".to_owned(), 1),
+                ("1/0".to_owned(), 2)
+            ]
+        );
+        assert_eq!(breadth_first, vec![(0, 1), (1, 10), (1, 2), (2, 99)]);
+    }
+
+    #[test]
+    fn inspection_works_for_deep_trees() {
+        // given
+        // a chain of nodes with one child and one metadata entry each
+        let depth = 200_000;
+        let mut numbers: Vec<usize> = Vec::with_capacity(depth * 3 + 3);
+        for _ in 0..depth {
+            numbers.extend_from_slice(&[1, 1]);
+        }
+        numbers.extend_from_slice(&[0, 1, 5]);
+        numbers.extend(std::iter::repeat_n(1, depth));
+        let tree = read_tree(&mut numbers.iter().copied()).unwrap();
+
+        // when
+        let depth_first_depths: Vec<usize> = depth_first(&tree).map(|(d, _)| d).collect();
+        let height = breadth_first(&tree).map(|(d, _)| d + 1).max();
+        let explanations = explain_values(&tree);
+        let mut nodes = depth_first(&tree);
+        let leaf = nodes.nth(depth).map(|(_, node)| node);
+        let leaf_path = nodes.path();
+
+        // then
+        assert_eq!(depth_first_depths, (0..=depth).collect::<Vec<usize>>());
+        assert_eq!(height, Some(depth + 1));
+        assert_eq!(explanations.len(), depth + 1);
+        assert!(explanations.iter().all(|e| e.value == 5));
+        assert_eq!(leaf_path, NodePath(vec![0; depth]));
+        assert_eq!(node_at(&tree, &leaf_path), leaf);
+        assert_eq!(leaf.map(|node| &node.metadata), Some(&vec![5]));
+    }
+
+    #[test]
+    fn node_at_follows_parsed_path() {
+        let tree = example_tree();
+        assert_eq!(
+            node_at(&tree, &parse_path("1/0").unwrap())
+                .unwrap()
+                .metadata,
+            vec![99]
+        );
+        assert_eq!(node_at(&tree, &parse_path("/").unwrap()), Some(&tree));
+        assert_eq!(node_at(&tree, &parse_path("0/0").unwrap()), None);
+        assert!(parse_path("1/x").is_err());
+    }
+
+    #[test]
+    fn pretty_print_explains_example() {
+        // given
+        let tree = example_tree();
+
+        // when
+        let output = pretty_print(&tree);
+
+        // then
+        assert_eq!(
+            output,
+            "/ [1, 1, 2] value 66: 1 -> child 0 = 33, 1 -> child 0 = 33, 2 -> child 1 = 0
+  0 [10, 11, 12] value 33 (leaf, sum of metadata)
+  1 [2] value 0: 2 -> no child = 0
+    1/0 [99] value 99 (leaf, sum of metadata)
+"
+        );
+    }
+}
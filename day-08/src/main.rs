@@ -3,14 +3,19 @@ use std::fs::File;
 use std::io::{BufReader, Read};
 use std::path::Path;
 
+mod inspect;
+
+use self::inspect::{breadth_first, node_at, parse_path, pretty_print};
+
 fn main() -> Result<(), String> {
     let filename = env::args().nth(1).ok_or("No file name given.".to_owned())?;
-    let content = read_file(&Path::new(&filename)).map_err(|e| e.to_string())?;
+    let content = read_file(Path::new(&filename)).map_err(|e| e.to_string())?;
 
-    let mut number_input = content
+    let numbers: Vec<usize> = content
         .split_whitespace()
-        .filter_map(|s| s.parse::<usize>().ok());
-    let tree = read_tree(&mut number_input)?;
+        .filter_map(|s| s.parse::<usize>().ok())
+        .collect();
+    let tree = read_complete_tree(&mut numbers.iter().copied())?;
 
     let metadata_sum = sum_metadata(&tree);
     println!("The sum of all metadata is {}", metadata_sum);
@@ -18,65 +23,153 @@ fn main() -> Result<(), String> {
     let root_value = node_value(&tree);
     println!("The value of the root node is {}", root_value);
 
+    // optionally show the subtree at the given path, e.g. "0/2/1"
+    if let Some(path) = env::args().nth(2) {
+        let path = parse_path(&path)?;
+        let subtree = node_at(&tree, &path).ok_or(format!("There is no node at {}", path))?;
+        let (n_nodes, height) =
+            breadth_first(subtree).fold((0, 0), |(n, _), (depth, _)| (n + 1, depth + 1));
+        println!(
+            "\nThe subtree at {} has {} nodes on {} levels:",
+            path, n_nodes, height
+        );
+        print!("{}", pretty_print(subtree));
+        // the subtree as a license file of its own
+        let encoded: Vec<String> = write_tree(subtree).iter().map(|n| n.to_string()).collect();
+        println!("\nEncoded subtree: {}", encoded.join(" "));
+    }
+
     Ok(())
 }
 
-fn sum_metadata(tree: &Node) -> usize {
-    let metadata_sum: usize = tree.metadata.iter().sum();
-    let children_metadata_sum: usize = tree.children.iter().map(|child| sum_metadata(child)).sum();
+// all functions on trees use explicit stacks instead of recursion, so that deep trees
+// do not overflow the call stack
 
-    return metadata_sum + children_metadata_sum;
+fn sum_metadata(tree: &Node) -> usize {
+    let mut sum: usize = 0;
+    let mut stack: Vec<&Node> = vec![tree];
+    while let Some(node) = stack.pop() {
+        sum += node.metadata.iter().sum::<usize>();
+        stack.extend(node.children.iter());
+    }
+    sum
 }
 
-fn node_value(node: &Node) -> usize {
-    if node.children.len() == 0 {
-        return node.metadata.iter().sum();
-    }
-    // Prof. Simon: "Rekursion kann töricht sein"
-    let mut lookup_children: Vec<Option<usize>> = (0..node.children.len()).map(|_| None).collect();
-    let mut sum: usize = 0;
-    for index in node
-        .metadata
-        .iter()
-        .filter(|i| **i <= node.children.len() && **i > 0)
-        .map(|i| i - 1)
-    {
-        if let Some(child_value) = lookup_children[index] {
-            sum += child_value;
+fn node_value(tree: &Node) -> usize {
+    // post-order traversal: a node is visited a second time after all of its children,
+    // at that point, the values of its children are on top of the value stack
+    let mut stack: Vec<(&Node, bool)> = vec![(tree, false)];
+    let mut values: Vec<usize> = Vec::new();
+    while let Some((node, children_done)) = stack.pop() {
+        if node.children.is_empty() {
+            values.push(node.metadata.iter().sum());
+        } else if !children_done {
+            stack.push((node, true));
+            stack.extend(node.children.iter().rev().map(|child| (child, false)));
         } else {
-            let child_value = node_value(&node.children[index]);
-            lookup_children[index] = Some(child_value);
-            sum += child_value;
+            let child_values = values.split_off(values.len() - node.children.len());
+            let value = node
+                .metadata
+                .iter()
+                .filter(|i| **i <= child_values.len() && **i > 0)
+                .map(|i| child_values[i - 1])
+                .sum();
+            values.push(value);
         }
     }
-    return sum;
+    values.pop().unwrap_or(0)
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Node {
+pub struct Node {
     children: Vec<Node>,
     metadata: Vec<usize>,
 }
 
+// the default drop would be recursive
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.children);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+// reads a tree and makes sure there is no input left afterwards
+fn read_complete_tree(input: &mut dyn Iterator<Item = usize>) -> Result<Node, String> {
+    let tree = read_tree(input)?;
+    if let Some(trailing) = input.next() {
+        return Err(format!(
+            "Unexpected input after the root node: {} (and {} more numbers)",
+            trailing,
+            input.count()
+        ));
+    }
+    Ok(tree)
+}
+
 fn read_tree(input: &mut dyn Iterator<Item = usize>) -> Result<Node, String> {
+    // for each node that is not finished yet: number of children still to read,
+    // number of metadata entries and the children read so far
+    let mut stack: Vec<(usize, usize, Vec<Node>)> = vec![read_header(input)?];
+    loop {
+        let Some((missing_children, _, _)) = stack.last_mut() else {
+            return Err("Unexpected empty stack".to_owned());
+        };
+        if *missing_children > 0 {
+            *missing_children -= 1;
+            let header = read_header(input)?;
+            stack.push(header);
+            continue;
+        }
+        let (_, n_metadata, children) = stack.pop().expect("stack is not empty");
+        let mut metadata = Vec::with_capacity(n_metadata);
+        for _ in 0..n_metadata {
+            let data = input
+                .next()
+                .ok_or_else(|| "Unexpected end of input, expected metadata".to_owned())?;
+            metadata.push(data);
+        }
+        let node = Node { children, metadata };
+        match stack.last_mut() {
+            Some((_, _, siblings)) => siblings.push(node),
+            None => return Ok(node),
+        }
+    }
+}
+
+fn read_header(
+    input: &mut dyn Iterator<Item = usize>,
+) -> Result<(usize, usize, Vec<Node>), String> {
     let n_children: usize = input
         .next()
         .ok_or_else(|| "Unexpected end of input, expected number of children".to_owned())?;
     let n_metadata: usize = input
         .next()
         .ok_or_else(|| "Unexpected end of input, expected meta data size".to_owned())?;
-    let mut children = Vec::with_capacity(n_children);
-    for _ in 0..n_children {
-        children.push(read_tree(input)?);
-    }
-    let mut metadata = Vec::with_capacity(n_metadata);
-    for _ in 0..n_metadata {
-        let data = input
-            .next()
-            .ok_or_else(|| "Unexpected end of input, expected metadata".to_owned())?;
-        metadata.push(data);
+    Ok((n_children, n_metadata, Vec::with_capacity(n_children)))
+}
+
+// the inverse of read_tree
+fn write_tree(tree: &Node) -> Vec<usize> {
+    let mut output: Vec<usize> = Vec::new();
+    // node and number of children already written
+    let mut stack: Vec<(&Node, usize)> = vec![(tree, 0)];
+    output.push(tree.children.len());
+    output.push(tree.metadata.len());
+    while let Some((node, written_children)) = stack.last_mut() {
+        if let Some(child) = node.children.get(*written_children) {
+            *written_children += 1;
+            output.push(child.children.len());
+            output.push(child.metadata.len());
+            stack.push((child, 0));
+        } else {
+            output.extend_from_slice(&node.metadata);
+            stack.pop();
+        }
     }
-    return Ok(Node { children, metadata });
+    output
 }
 
 fn read_file(path: &Path) -> std::io::Result<String> {
@@ -84,7 +177,7 @@ fn read_file(path: &Path) -> std::io::Result<String> {
     let mut bufr = BufReader::new(ifile);
     let mut result = String::with_capacity(2048);
     bufr.read_to_string(&mut result)?;
-    return Ok(result);
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -96,7 +189,7 @@ mod test {
     #[test]
     fn read_tree_should_parse_example_tree() {
         // given
-        let mut input = EXAMPLE_INPUT.iter().map(|u| *u);
+        let mut input = EXAMPLE_INPUT.iter().copied();
 
         // when
         let tree = read_tree(&mut input).unwrap();
@@ -121,7 +214,7 @@ mod test {
     #[test]
     fn sum_metadata_should_work_for_example() {
         // given
-        let mut input = EXAMPLE_INPUT.iter().map(|u| *u);
+        let mut input = EXAMPLE_INPUT.iter().copied();
         let tree = read_tree(&mut input).unwrap();
 
         // when
@@ -134,7 +227,7 @@ mod test {
     #[test]
     fn node_value_should_work_for_example() {
         // given
-        let mut input = EXAMPLE_INPUT.iter().map(|u| *u);
+        let mut input = EXAMPLE_INPUT.iter().copied();
         let tree = read_tree(&mut input).unwrap();
 
         // when
@@ -143,4 +236,47 @@ mod test {
         // then
         assert_eq!(value, 66);
     }
+
+    #[test]
+    fn write_tree_is_inverse_of_read_tree() {
+        // given
+        let mut input = EXAMPLE_INPUT.iter().copied();
+        let tree = read_tree(&mut input).unwrap();
+
+        // when
+        let output = write_tree(&tree);
+
+        // then
+        assert_eq!(output, EXAMPLE_INPUT);
+    }
+
+    #[test]
+    fn read_complete_tree_rejects_trailing_input() {
+        let mut input = EXAMPLE_INPUT.iter().copied().chain([7, 8]);
+        assert!(read_complete_tree(&mut input).is_err());
+
+        let mut input = EXAMPLE_INPUT.iter().copied();
+        assert!(read_complete_tree(&mut input).is_ok());
+    }
+
+    #[test]
+    fn tree_functions_work_for_deep_trees() {
+        // given
+        // a chain of nodes with one child and one metadata entry each
+        let depth = 200_000;
+        let mut numbers: Vec<usize> = Vec::with_capacity(depth * 3 + 3);
+        for _ in 0..depth {
+            numbers.extend_from_slice(&[1, 1]);
+        }
+        numbers.extend_from_slice(&[0, 1, 5]);
+        numbers.extend(std::iter::repeat_n(1, depth));
+
+        // when
+        let tree = read_complete_tree(&mut numbers.iter().copied()).unwrap();
+
+        // then
+        assert_eq!(sum_metadata(&tree), depth + 5);
+        assert_eq!(node_value(&tree), 5);
+        assert!(write_tree(&tree) == numbers);
+    }
 }
@@ -1,87 +1,259 @@
-// a circular, non-empty double linked list (in a way)
+// the operations the marble game needs
+pub trait Circle<T> {
+    fn with_capacity(capacity: usize, first_element: T) -> Self;
+    fn len(&self) -> usize;
+    fn move_right_n(&mut self, n: usize);
+    fn move_left_n(&mut self, n: usize);
+    // insert a value right of the current element
+    fn insert_right(&mut self, value: T);
+    // remove the current element and use the element to the right as new current element
+    fn remove_use_right(&mut self) -> Option<T>;
+    // the element `offset` steps right of the current one
+    fn get(&self, offset: usize) -> Option<&T>;
+}
+
+// a circular double linked list in a vector. Slots of removed elements are kept in a free list
+// and reused by later insertions.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct CircularList<T> {
     data: Vec<CircularListElement<T>>,
-    current: usize,
+    free: Vec<usize>,
+    // None if the list is empty
+    current: Option<usize>,
+    len: usize,
 }
 
 impl<T> CircularList<T> {
+    pub fn new() -> CircularList<T> {
+        CircularList {
+            data: Vec::new(),
+            free: Vec::new(),
+            current: None,
+            len: 0,
+        }
+    }
+
     pub fn with_capacity(capacity: usize, first_element: T) -> CircularList<T> {
-        let mut elements = Vec::with_capacity(capacity);
-        elements.push(CircularListElement {
-            payload: first_element,
-            left_index: 0,
-            right_index: 0,
-        });
-        return CircularList {
-            data: elements,
-            current: 0,
+        let mut list = CircularList {
+            data: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            current: None,
+            len: 0,
         };
+        list.insert_right(first_element);
+        list
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     pub fn move_right(&mut self) {
-        self.current = self.data[self.current].right_index;
+        if let Some(current) = self.current {
+            self.current = Some(self.data[current].right_index);
+        }
     }
 
+    // moves at most half way around the circle, in whichever direction is shorter
     pub fn move_right_n(&mut self, n: usize) {
-        for _ in 0..n {
-            self.move_right();
+        if self.is_empty() {
+            return;
+        }
+        let n = n % self.len;
+        if n <= self.len / 2 {
+            (0..n).for_each(|_| self.move_right());
+        } else {
+            (n..self.len).for_each(|_| self.move_left());
         }
     }
 
     pub fn move_left(&mut self) {
-        self.current = self.data[self.current].left_index;
+        if let Some(current) = self.current {
+            self.current = Some(self.data[current].left_index);
+        }
     }
 
     pub fn move_left_n(&mut self, n: usize) {
-        for _ in 0..n {
-            self.move_left();
+        if self.is_empty() {
+            return;
         }
+        self.move_right_n(self.len - n % self.len);
     }
 
+    // insert a value right of the current element, in an empty list, the value becomes the
+    // current element
     pub fn insert_right(&mut self, value: T) {
-        let left_index = self.current;
-        let right_index = self.data[self.current].right_index;
-        let new_index = self.data.len();
-        self.data.push(CircularListElement {
-            payload: value,
+        let Some(current) = self.current else {
+            let index = self.allocate(value, 0, 0);
+            self.data[index].left_index = index;
+            self.data[index].right_index = index;
+            self.current = Some(index);
+            return;
+        };
+        let right_index = self.data[current].right_index;
+        let new_index = self.allocate(value, current, right_index);
+        self.data[right_index].left_index = new_index;
+        self.data[current].right_index = new_index;
+    }
+
+    // remove the current element and use the element to the right as new current element,
+    // returns None if the list is empty
+    pub fn remove_use_right(&mut self) -> Option<T> {
+        let current = self.current?;
+        let right_index = self.data[current].right_index;
+        let value = self.unlink(current);
+        self.current = (right_index != current).then_some(right_index);
+        Some(value)
+    }
+
+    pub fn current_value(&self) -> Option<&T> {
+        self.current
+            .and_then(|current| self.data[current].payload.as_ref())
+    }
+
+    // all values, starting with the current one and going right. Going backwards (with `rev`)
+    // starts with the value to the left of the current one and goes left.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let front = self.current.unwrap_or(0);
+        let back = self
+            .current
+            .map(|current| self.data[current].left_index)
+            .unwrap_or(0);
+        Iter {
+            list: self,
+            front,
+            back,
+            remaining: self.len,
+        }
+    }
+
+    fn allocate(&mut self, value: T, left_index: usize, right_index: usize) -> usize {
+        let element = CircularListElement {
+            payload: Some(value),
             left_index,
             right_index,
-        });
-        self.data[right_index].left_index = new_index;
-        self.data[self.current].right_index = new_index;
-    }
-
-    // remove the current element and use the element to the left as new current element
-    // IMPORTANT: this does not really remove the value (frees space) but only removes its
-    // place in the circle
-    // returns a borrow of the removed value
-    // also, cannot remove the last element and will panic if trying to
-    pub fn remove_use_right(&mut self) -> &T {
-        let right_index = self.data[self.current].right_index;
-        let left_index = self.data[self.current].left_index;
-        let old_index = self.current;
-        if right_index == old_index {
-            panic!("Cannot remove last element!");
+        };
+        self.len += 1;
+        match self.free.pop() {
+            Some(index) => {
+                self.data[index] = element;
+                index
+            }
+            None => {
+                self.data.push(element);
+                self.data.len() - 1
+            }
         }
+    }
+
+    fn unlink(&mut self, index: usize) -> T {
+        let right_index = self.data[index].right_index;
+        let left_index = self.data[index].left_index;
         self.data[right_index].left_index = left_index;
         self.data[left_index].right_index = right_index;
-        self.current = right_index;
-        return &self.data[old_index].payload;
+        self.free.push(index);
+        self.len -= 1;
+        self.data[index]
+            .payload
+            .take()
+            .expect("linked elements have a payload")
+    }
+}
+
+impl<T> Circle<T> for CircularList<T> {
+    fn with_capacity(capacity: usize, first_element: T) -> Self {
+        CircularList::with_capacity(capacity, first_element)
+    }
+
+    fn len(&self) -> usize {
+        CircularList::len(self)
+    }
+
+    fn move_right_n(&mut self, n: usize) {
+        CircularList::move_right_n(self, n);
+    }
+
+    fn move_left_n(&mut self, n: usize) {
+        CircularList::move_left_n(self, n);
+    }
+
+    fn insert_right(&mut self, value: T) {
+        CircularList::insert_right(self, value);
+    }
+
+    fn remove_use_right(&mut self) -> Option<T> {
+        CircularList::remove_use_right(self)
+    }
+
+    // takes O(offset) steps
+    fn get(&self, offset: usize) -> Option<&T> {
+        if self.is_empty() {
+            return None;
+        }
+        match offset % self.len() {
+            0 => self.current_value(),
+            offset => self.iter().nth(offset),
+        }
     }
+}
 
-    pub fn current_value(&self) -> &T {
-        &self.data[self.current].payload
+impl<T> Default for CircularList<T> {
+    fn default() -> CircularList<T> {
+        CircularList::new()
     }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 struct CircularListElement<T> {
-    payload: T,
+    // None for free slots
+    payload: Option<T>,
     left_index: usize,
     right_index: usize,
 }
 
+pub struct Iter<'a, T> {
+    list: &'a CircularList<T>,
+    front: usize,
+    back: usize,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let element = &self.list.data[self.front];
+        self.front = element.right_index;
+        element.payload.as_ref()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let element = &self.list.data[self.back];
+        self.back = element.left_index;
+        element.payload.as_ref()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -92,11 +264,11 @@ mod test {
         let mut circle: CircularList<u32> = CircularList::with_capacity(10, 42);
 
         // when/then
-        assert_eq!(circle.current_value(), &42);
+        assert_eq!(circle.current_value(), Some(&42));
         circle.move_right();
-        assert_eq!(circle.current_value(), &42);
+        assert_eq!(circle.current_value(), Some(&42));
         circle.move_left();
-        assert_eq!(circle.current_value(), &42);
+        assert_eq!(circle.current_value(), Some(&42));
     }
 
     #[test]
@@ -109,13 +281,13 @@ mod test {
         circle.insert_right(1337);
 
         // then
-        assert_eq!(circle.current_value(), &42);
+        assert_eq!(circle.current_value(), Some(&42));
         circle.move_right();
-        assert_eq!(circle.current_value(), &1337);
+        assert_eq!(circle.current_value(), Some(&1337));
         circle.move_right();
-        assert_eq!(circle.current_value(), &9001);
+        assert_eq!(circle.current_value(), Some(&9001));
         circle.move_right();
-        assert_eq!(circle.current_value(), &42);
+        assert_eq!(circle.current_value(), Some(&42));
     }
 
     #[test]
@@ -126,14 +298,15 @@ mod test {
         circle.insert_right(1337);
 
         // when
-        circle.remove_use_right();
+        let removed = circle.remove_use_right();
 
         // then
-        assert_eq!(circle.current_value(), &1337);
+        assert_eq!(removed, Some(42));
+        assert_eq!(circle.current_value(), Some(&1337));
         circle.move_left();
-        assert_eq!(circle.current_value(), &9001);
+        assert_eq!(circle.current_value(), Some(&9001));
         circle.move_left();
-        assert_eq!(circle.current_value(), &1337);
+        assert_eq!(circle.current_value(), Some(&1337));
     }
 
     #[test]
@@ -146,7 +319,9 @@ mod test {
 
         // when
         circle.move_right_n(2);
-        assert_eq!(circle.current_value(), &1337);
+        assert_eq!(circle.current_value(), Some(&1337));
+        circle.move_right_n(7);
+        assert_eq!(circle.current_value(), Some(&313));
     }
 
     #[test]
@@ -161,6 +336,65 @@ mod test {
         circle.move_left_n(2);
 
         // then
-        assert_eq!(circle.current_value(), &1337);
+        assert_eq!(circle.current_value(), Some(&1337));
+    }
+
+    #[test]
+    fn removing_all_elements_empties_the_list() {
+        // given
+        let mut circle: CircularList<u32> = CircularList::new();
+        circle.insert_right(1);
+        circle.insert_right(2);
+
+        // when/then
+        assert_eq!(circle.len(), 2);
+        assert_eq!(circle.remove_use_right(), Some(1));
+        assert_eq!(circle.remove_use_right(), Some(2));
+        assert!(circle.is_empty());
+        assert_eq!(circle.remove_use_right(), None);
+        assert_eq!(circle.current_value(), None);
+        circle.move_left_n(3);
+        assert_eq!(circle.iter().next(), None);
+    }
+
+    #[test]
+    fn removed_slots_are_reused() {
+        // given
+        let mut circle: CircularList<u32> = CircularList::with_capacity(10, 0);
+        for value in 1..5 {
+            circle.insert_right(value);
+        }
+
+        // when
+        for _ in 0..3 {
+            circle.remove_use_right();
+        }
+        for value in 5..8 {
+            circle.insert_right(value);
+        }
+
+        // then
+        assert_eq!(circle.data.len(), 5);
+        assert_eq!(circle.len(), 5);
+        let values: Vec<u32> = circle.iter().copied().collect();
+        assert_eq!(values, vec![2, 7, 6, 5, 1]);
+    }
+
+    #[test]
+    fn iter_works_in_both_directions() {
+        // given
+        let mut circle: CircularList<u32> = CircularList::with_capacity(10, 1);
+        circle.insert_right(4);
+        circle.insert_right(3);
+        circle.insert_right(2);
+
+        // when
+        let right: Vec<u32> = circle.iter().copied().collect();
+        let left: Vec<u32> = circle.iter().rev().copied().collect();
+
+        // then
+        assert_eq!(right, vec![1, 2, 3, 4]);
+        assert_eq!(left, vec![4, 3, 2, 1]);
+        assert_eq!(circle.iter().len(), 4);
     }
 }
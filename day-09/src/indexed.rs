@@ -0,0 +1,311 @@
+use super::circle::Circle;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+// a circle as a sequence in clockwise order together with the index of the current element.
+// Moving the current position only changes that index, so it takes constant time for any
+// number of steps. The sequence is an implicit treap, so indexed access relative to the current
+// element, inserting next to it and removing it take O(log n) expected time. For the short moves
+// of the standard marble game, RotatingCircle is faster.
+#[derive(Debug, Clone)]
+pub struct IndexedCircle<T> {
+    sequence: Sequence<T>,
+    current: usize,
+}
+
+impl<T> IndexedCircle<T> {
+    pub fn with_capacity(capacity: usize, first_element: T) -> IndexedCircle<T> {
+        let mut sequence = Sequence::with_capacity(capacity);
+        sequence.insert(0, first_element);
+        IndexedCircle {
+            sequence,
+            current: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // the element `offset` steps right of the current one, 0 is the current element
+    pub fn get(&self, offset: usize) -> Option<&T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.sequence
+            .get((self.current + offset % self.len()) % self.len())
+    }
+
+    pub fn rotate_right(&mut self, n: usize) {
+        if !self.is_empty() {
+            self.current = (self.current + n % self.len()) % self.len();
+        }
+    }
+
+    pub fn rotate_left(&mut self, n: usize) {
+        if !self.is_empty() {
+            self.current = (self.current + self.len() - n % self.len()) % self.len();
+        }
+    }
+
+    // insert a value right of the current element
+    pub fn insert_right(&mut self, value: T) {
+        if self.is_empty() {
+            self.sequence.insert(0, value);
+            self.current = 0;
+        } else {
+            self.sequence.insert(self.current + 1, value);
+        }
+    }
+
+    // remove the current element and use the element to the right as new current element
+    pub fn remove_use_right(&mut self) -> Option<T> {
+        let removed = self.sequence.remove(self.current)?;
+        if self.current == self.len() {
+            self.current = 0;
+        }
+        Some(removed)
+    }
+}
+
+// indices of nodes are u32 to keep the nodes small, this marks a missing child
+const NO_NODE: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+struct TreapNode<T> {
+    // None for nodes that were removed and can be reused
+    value: Option<T>,
+    priority: u32,
+    // the number of nodes in the subtree of this node
+    size: u32,
+    left: u32,
+    right: u32,
+}
+
+// A sequence in a treap that is ordered by position: all nodes left of a node come before it.
+// The random priorities keep the tree balanced. The nodes are stored in a Vec and referenced by
+// index, the nodes of removed elements are reused.
+#[derive(Debug, Clone)]
+struct Sequence<T> {
+    nodes: Vec<TreapNode<T>>,
+    free: Vec<u32>,
+    root: u32,
+    // state of the xorshift generator for the priorities
+    random: u32,
+}
+
+impl<T> Sequence<T> {
+    fn with_capacity(capacity: usize) -> Sequence<T> {
+        Sequence {
+            nodes: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            root: NO_NODE,
+            random: 0x9e37_79b9,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.size(self.root) as usize
+    }
+
+    fn size(&self, node: u32) -> u32 {
+        if node == NO_NODE {
+            0
+        } else {
+            self.nodes[node as usize].size
+        }
+    }
+
+    fn update_size(&mut self, node: u32) {
+        let TreapNode { left, right, .. } = self.nodes[node as usize];
+        self.nodes[node as usize].size = 1 + self.size(left) + self.size(right);
+    }
+
+    // split the subtree into the first n elements and the rest
+    fn split(&mut self, node: u32, n: u32) -> (u32, u32) {
+        if node == NO_NODE {
+            return (NO_NODE, NO_NODE);
+        }
+        let TreapNode { left, right, .. } = self.nodes[node as usize];
+        let left_size = self.size(left);
+        if n <= left_size {
+            let (first, rest) = self.split(left, n);
+            self.nodes[node as usize].left = rest;
+            self.update_size(node);
+            (first, node)
+        } else {
+            let (first, rest) = self.split(right, n - left_size - 1);
+            self.nodes[node as usize].right = first;
+            self.update_size(node);
+            (node, rest)
+        }
+    }
+
+    // all elements of `left` followed by all elements of `right`
+    fn merge(&mut self, left: u32, right: u32) -> u32 {
+        if left == NO_NODE {
+            return right;
+        }
+        if right == NO_NODE {
+            return left;
+        }
+        if self.nodes[left as usize].priority > self.nodes[right as usize].priority {
+            let merged = self.merge(self.nodes[left as usize].right, right);
+            self.nodes[left as usize].right = merged;
+            self.update_size(left);
+            left
+        } else {
+            let merged = self.merge(left, self.nodes[right as usize].left);
+            self.nodes[right as usize].left = merged;
+            self.update_size(right);
+            right
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        let mut index = u32::try_from(index).ok()?;
+        let mut node = self.root;
+        while node != NO_NODE {
+            let current = &self.nodes[node as usize];
+            let left_size = self.size(current.left);
+            match index.cmp(&left_size) {
+                Ordering::Less => node = current.left,
+                Ordering::Equal => return current.value.as_ref(),
+                Ordering::Greater => {
+                    index -= left_size + 1;
+                    node = current.right;
+                }
+            }
+        }
+        None
+    }
+
+    // insert the value so that it has the given index, index may be the length
+    fn insert(&mut self, index: usize, value: T) {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 17;
+        self.random ^= self.random << 5;
+        let node = TreapNode {
+            value: Some(value),
+            priority: self.random,
+            size: 1,
+            left: NO_NODE,
+            right: NO_NODE,
+        };
+        let new = match self.free.pop() {
+            Some(free) => {
+                self.nodes[free as usize] = node;
+                free
+            }
+            None => {
+                self.nodes.push(node);
+                u32::try_from(self.nodes.len() - 1)
+                    .ok()
+                    .filter(|new| *new != NO_NODE)
+                    .expect("a circle has less than 2^32 - 1 elements")
+            }
+        };
+        let (first, rest) = self.split(self.root, index as u32);
+        let first = self.merge(first, new);
+        self.root = self.merge(first, rest);
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let (first, rest) = self.split(self.root, index as u32);
+        let (removed, rest) = self.split(rest, 1);
+        self.root = self.merge(first, rest);
+        self.free.push(removed);
+        self.nodes[removed as usize].value.take()
+    }
+}
+
+impl<T> Circle<T> for IndexedCircle<T> {
+    fn with_capacity(capacity: usize, first_element: T) -> Self {
+        IndexedCircle::with_capacity(capacity, first_element)
+    }
+
+    fn len(&self) -> usize {
+        IndexedCircle::len(self)
+    }
+
+    fn move_right_n(&mut self, n: usize) {
+        self.rotate_right(n);
+    }
+
+    fn move_left_n(&mut self, n: usize) {
+        self.rotate_left(n);
+    }
+
+    fn insert_right(&mut self, value: T) {
+        IndexedCircle::insert_right(self, value);
+    }
+
+    fn remove_use_right(&mut self) -> Option<T> {
+        IndexedCircle::remove_use_right(self)
+    }
+
+    fn get(&self, offset: usize) -> Option<&T> {
+        IndexedCircle::get(self, offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn circle_matches_vec_for_random_operations() {
+        // given
+        let mut circle: IndexedCircle<u64> = IndexedCircle::with_capacity(0, 0);
+        let mut expected: Vec<u64> = vec![0];
+        let mut current: usize = 0;
+        let mut random: u64 = 1;
+
+        // when/then
+        for value in 1..5000 {
+            random = random
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let steps = (random >> 20) as usize;
+            match random >> 61 {
+                0 | 1 if !expected.is_empty() => {
+                    circle.rotate_right(steps);
+                    current = (current + steps) % expected.len();
+                }
+                2 if !expected.is_empty() => {
+                    circle.rotate_left(steps);
+                    current = (current + expected.len() - steps % expected.len()) % expected.len();
+                }
+                3 => {
+                    let removed = (!expected.is_empty()).then(|| expected.remove(current));
+                    if current == expected.len() {
+                        current = 0;
+                    }
+                    assert_eq!(circle.remove_use_right(), removed);
+                }
+                _ => {
+                    if expected.is_empty() {
+                        expected.push(value);
+                    } else {
+                        expected.insert(current + 1, value);
+                    }
+                    circle.insert_right(value);
+                }
+            }
+            assert_eq!(circle.len(), expected.len());
+            if !expected.is_empty() {
+                assert_eq!(
+                    circle.get(steps),
+                    expected.get((current + steps) % expected.len())
+                );
+            }
+        }
+    }
+}
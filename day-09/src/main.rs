@@ -1,20 +1,88 @@
 mod circle;
+mod indexed;
+mod periodic;
+mod rotating;
+mod rules;
 
-use self::circle::CircularList;
+use self::circle::{Circle, CircularList};
+use self::indexed::IndexedCircle;
+use self::periodic::periodic_scores;
+use self::rotating::RotatingCircle;
+use self::rules::{Event, Rules};
+use std::env;
 
 fn main() -> Result<(), String> {
+    // the first argument is the mode, all further arguments change the rules, e.g. "special=19",
+    // or the factor for the number of marbles in the second game, e.g. "factor=10000"
+    let mode = env::args().nth(1);
+    let mut rules = Rules::default();
+    let mut factor: usize = 100;
+    for option in env::args().skip(2) {
+        match option.strip_prefix("factor=") {
+            Some(value) => {
+                factor = value
+                    .parse()
+                    .map_err(|_| format!("Invalid factor '{}'", value))?
+            }
+            None => rules.set_option(&option)?,
+        }
+    }
+
+    // show the turns of the example game like in the puzzle description
+    if mode.as_deref() == Some("show") {
+        let mut state: State<RotatingCircle<usize>> = State::new(9, 26, rules);
+        println!("[-] {}", circle_line(&state.circle));
+        while state.next_marble < 26 {
+            let (next_state, event) = state.turn();
+            state = next_state;
+            println!("[{}] {}", event.player + 1, circle_line(&state.circle));
+        }
+        println!("{} marbles are left in the circle", state.circle.len());
+        return Ok(());
+    }
+
     // hardcode the input here, no need to read it from file
     let n_players: usize = 430;
     let highest_marble_puzzle_1: usize = 71588;
 
-    let score1 = winning_score(n_players, highest_marble_puzzle_1 + 1);
+    // all scoring turns of the first game as CSV
+    if mode.as_deref() == Some("events") {
+        println!("turn,player,marble,points,removed");
+        play_game::<RotatingCircle<usize>>(
+            n_players,
+            highest_marble_puzzle_1 + 1,
+            rules,
+            &mut |event| {
+                if let Some(removed) = event.removed {
+                    println!(
+                        "{},{},{},{},{}",
+                        event.turn, event.player, event.marble, event.points, removed
+                    );
+                }
+            },
+        );
+        return Ok(());
+    }
+
+    // the rotating circle is the default, the indexed circle is for rules with long moves and the
+    // linked list is there for comparison. The periodic solver only works for the standard rules.
+    let winning_score = match mode.as_deref() {
+        None | Some("rotating") => winning_score::<RotatingCircle<usize>>,
+        Some("indexed") => winning_score::<IndexedCircle<usize>>,
+        Some("list") => winning_score::<CircularList<usize>>,
+        Some("periodic") if rules == Rules::default() => periodic_winning_score,
+        Some("periodic") => return Err("The periodic solver needs the standard rules".to_owned()),
+        Some(other) => return Err(format!("Unknown mode '{}'", other)),
+    };
+
+    let score1 = winning_score(n_players, highest_marble_puzzle_1 + 1, rules);
     println!(
         "For {} players with {} marbles, the highest score is {}",
         n_players, highest_marble_puzzle_1, score1
     );
 
-    let highest_marble_puzzle_2 = highest_marble_puzzle_1 * 100;
-    let score2 = winning_score(n_players, highest_marble_puzzle_2 + 1);
+    let highest_marble_puzzle_2 = highest_marble_puzzle_1 * factor;
+    let score2 = winning_score(n_players, highest_marble_puzzle_2 + 1, rules);
     println!(
         "For {} players with {} marbles, the highest score is {}",
         n_players, highest_marble_puzzle_2, score2
@@ -23,35 +91,73 @@ fn main() -> Result<(), String> {
     Ok(())
 }
 
-fn winning_score(n_players: usize, n_marbles: usize) -> usize {
-    play_game(n_players, n_marbles)
+fn winning_score<C: Circle<usize>>(n_players: usize, n_marbles: usize, rules: Rules) -> usize {
+    play_game::<C>(n_players, n_marbles, rules, &mut |_| ())
         .into_iter()
         .max()
         .expect("0 players can't play a game.")
 }
 
-fn play_game(n_players: usize, n_marbles: usize) -> Vec<usize> {
-    let mut state = State::new(n_players, n_marbles);
+fn periodic_winning_score(n_players: usize, n_marbles: usize, _rules: Rules) -> usize {
+    periodic_scores(n_players, n_marbles)
+        .into_iter()
+        .max()
+        .expect("0 players can't play a game.")
+}
+
+// the points of each player, on_event is called after every turn
+fn play_game<C: Circle<usize>>(
+    n_players: usize,
+    n_marbles: usize,
+    rules: Rules,
+    on_event: &mut dyn FnMut(&Event),
+) -> Vec<usize> {
+    let mut state: State<C> = State::new(n_players, n_marbles, rules);
     while state.next_marble < n_marbles {
-        state = state.turn();
+        let (next_state, event) = state.turn();
+        on_event(&event);
+        state = next_state;
     }
-    return state.points;
+    state.points
+}
+
+// the marbles clockwise, starting with marble 0, the current marble is in parentheses
+fn circle_line<C: Circle<usize>>(circle: &C) -> String {
+    // offset 0 is the current marble
+    let mut marbles: Vec<String> = (0..circle.len())
+        .filter_map(|offset| circle.get(offset))
+        .enumerate()
+        .map(|(offset, marble)| {
+            if offset == 0 {
+                format!("({})", marble)
+            } else {
+                marble.to_string()
+            }
+        })
+        .collect();
+    let zero_position = (0..circle.len())
+        .position(|offset| circle.get(offset) == Some(&0))
+        .unwrap_or(0);
+    marbles.rotate_left(zero_position);
+    marbles.join(" ")
 }
 
-struct State {
+struct State<C> {
     n_players: usize,
-    circle: CircularList<usize>,
+    rules: Rules,
+    circle: C,
     current_player: usize,
     points: Vec<usize>,
     next_marble: usize,
 }
 
-impl State {
-    fn new(n_players: usize, n_marbles: usize) -> State {
-        let circle: CircularList<usize> = CircularList::with_capacity(n_marbles, 0);
+impl<C: Circle<usize>> State<C> {
+    fn new(n_players: usize, n_marbles: usize, rules: Rules) -> State<C> {
+        let circle = C::with_capacity(n_marbles, 0);
         let points: Vec<usize> = (0..n_players).map(|_| 0).collect();
         State {
             n_players,
+            rules,
             circle,
             current_player: 0,
             points,
@@ -59,24 +165,38 @@ impl State {
         }
     }
 
-    fn turn(mut self) -> State {
-        if self.next_marble % 23 != 0 {
-            self.circle.move_right();
+    fn turn(mut self) -> (State<C>, Event) {
+        let mut event = Event {
+            turn: self.next_marble,
+            player: self.current_player,
+            marble: self.next_marble,
+            points: 0,
+            removed: None,
+        };
+        if !self.next_marble.is_multiple_of(self.rules.special_marble) {
+            self.circle.move_right_n(self.rules.insertion_step);
             self.circle.insert_right(self.next_marble);
-            self.circle.move_right();
+            self.circle.move_right_n(1);
         } else {
-            self.points[self.current_player] += self.next_marble;
-            self.circle.move_left_n(7);
-            self.points[self.current_player] += *self.circle.remove_use_right();
+            self.circle.move_left_n(self.rules.removal_offset);
+            // rules built without set_option can empty the circle, then there is nothing to
+            // score and the special marble is still not placed
+            if let Some(removed) = self.circle.remove_use_right() {
+                event.points = self.next_marble + removed;
+                event.removed = Some(removed);
+                self.points[self.current_player] += event.points;
+            }
         };
         let next_player = (self.current_player + 1) % self.n_players;
-        State {
+        let state = State {
             n_players: self.n_players,
+            rules: self.rules,
             circle: self.circle,
             current_player: next_player,
             points: self.points,
             next_marble: self.next_marble + 1,
-        }
+        };
+        (state, event)
     }
 }
 
@@ -84,13 +204,119 @@ impl State {
 mod test {
     use super::*;
 
+    #[test]
+    fn circle_line_shows_example_state() {
+        // given
+        let mut list_state: State<CircularList<usize>> = State::new(9, 26, Rules::default());
+        let mut rotating_state: State<RotatingCircle<usize>> = State::new(9, 26, Rules::default());
+
+        // when
+        for _ in 0..23 {
+            list_state = list_state.turn().0;
+            rotating_state = rotating_state.turn().0;
+        }
+
+        // then
+        assert_eq!(
+            circle_line(&rotating_state.circle),
+            circle_line(&list_state.circle)
+        );
+        assert_eq!(
+            circle_line(&list_state.circle),
+            "0 16 8 17 4 18 (19) 2 20 10 21 5 22 11 1 12 6 13 3 14 7 15"
+        );
+    }
+
     #[test]
     fn winning_score_for_examples() {
-        assert_eq!(winning_score(9, 25 + 1), 32);
-        assert_eq!(winning_score(10, 1618 + 1), 8317);
-        assert_eq!(winning_score(13, 7999 + 1), 146373);
-        assert_eq!(winning_score(17, 1104 + 1), 2764);
-        assert_eq!(winning_score(21, 6111 + 1), 54718);
-        assert_eq!(winning_score(30, 5807 + 1), 37305);
+        let examples = [
+            (9, 25 + 1, 32),
+            (10, 1618 + 1, 8317),
+            (13, 7999 + 1, 146373),
+            (17, 1104 + 1, 2764),
+            (21, 6111 + 1, 54718),
+            (30, 5807 + 1, 37305),
+        ];
+        for (n_players, n_marbles, score) in examples.iter().copied() {
+            assert_eq!(
+                winning_score::<CircularList<usize>>(n_players, n_marbles, Rules::default()),
+                score
+            );
+            assert_eq!(
+                winning_score::<RotatingCircle<usize>>(n_players, n_marbles, Rules::default()),
+                score
+            );
+        }
+    }
+
+    #[test]
+    fn play_game_reports_scoring_event() {
+        // given
+        let mut events: Vec<Event> = Vec::new();
+
+        // when
+        let points = play_game::<RotatingCircle<usize>>(9, 26, Rules::default(), &mut |event| {
+            events.push(*event)
+        });
+
+        // then
+        assert_eq!(events.len(), 25);
+        assert_eq!(
+            events[22],
+            Event {
+                turn: 23,
+                player: 4,
+                marble: 23,
+                points: 32,
+                removed: Some(9),
+            }
+        );
+        assert_eq!(events.iter().map(|e| e.points).sum::<usize>(), 32);
+        assert_eq!(points[4], 32);
+    }
+
+    #[test]
+    fn turn_does_not_score_on_empty_circle() {
+        // given
+        let rules = Rules {
+            special_marble: 1,
+            insertion_step: 1,
+            removal_offset: 7,
+        };
+
+        // when/then
+        // marble 1 removes marble 0, the circle is empty for all later turns
+        assert_eq!(
+            play_game::<RotatingCircle<usize>>(2, 5, rules, &mut |_| ()),
+            vec![1, 0]
+        );
+        assert_eq!(
+            play_game::<CircularList<usize>>(2, 5, rules, &mut |_| ()),
+            vec![1, 0]
+        );
+        assert_eq!(
+            play_game::<IndexedCircle<usize>>(2, 5, rules, &mut |_| ()),
+            vec![1, 0]
+        );
+    }
+
+    #[test]
+    fn rule_variants_agree_for_all_circles() {
+        // given
+        let rules = Rules {
+            special_marble: 11,
+            insertion_step: 3,
+            removal_offset: 5,
+        };
+
+        // when
+        let list_points = play_game::<CircularList<usize>>(7, 2000, rules, &mut |_| ());
+        let rotating_points = play_game::<RotatingCircle<usize>>(7, 2000, rules, &mut |_| ());
+        let indexed_points = play_game::<IndexedCircle<usize>>(7, 2000, rules, &mut |_| ());
+
+        // then
+        assert_eq!(list_points, rotating_points);
+        assert_eq!(list_points, indexed_points);
+        assert!(list_points.iter().sum::<usize>() > 0);
     }
 }
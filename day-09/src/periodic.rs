@@ -0,0 +1,170 @@
+use super::rotating::RotatingCircle;
+use super::rules::Rules;
+use super::State;
+use std::thread;
+
+// Solver for the standard rules that does not store the circle.
+//
+// Look at the circle as the current marble followed by a queue of the other marbles in clockwise
+// order. A normal turn takes the first marble of the queue and appends the old current marble and
+// that first marble to the end of the queue; the new marble becomes the current one. After 22
+// normal turns that took p1, ..., p22 from the queue, the end of the queue is
+// ..., p18, m+18, p19, m+19, p20, m+20, p21, m+21, p22 (m is the previous multiple of 23).
+// The 23rd turn removes p19, the marbles from m+19 on move to the front and m+19 becomes the
+// current marble.
+//
+// So in every period, p1 to p6 are p20, m+20, p21, m+21, p22, m+22 of the previous period, and
+// p7 to p22 are the next 16 marbles of the queue. The period appends the old current marble, p1,
+// m+1, p2, ..., p18, m+18 to the queue. Every marble of the queue is either one of the marbles
+// m+x or a copy of an earlier queue marble, which is looked up instead of stored.
+
+const PERIOD: usize = 23;
+// the first two periods are simulated, afterwards the queue is long enough that a period never
+// takes a marble it appended itself
+const WARMUP_MARBLES: usize = 2 * PERIOD + 1;
+// marbles taken from the queue in each period (p7 to p22)
+const TAKEN: usize = 16;
+// marbles appended to the queue in each period
+const APPENDED: usize = 37;
+
+enum Marble {
+    Value(usize),
+    QueueIndex(usize),
+}
+
+struct Queue {
+    // the state after the warmup
+    first_current: usize,
+    first_taken: Vec<usize>,
+    start: Vec<usize>,
+}
+
+impl Queue {
+    // the multiple of 23 before the given period
+    fn previous_special(period: usize) -> usize {
+        WARMUP_MARBLES - 1 + PERIOD * period
+    }
+
+    // the t-th marble (1 to 22) taken in the given period
+    fn taken(&self, period: usize, t: usize) -> Marble {
+        if t > 6 {
+            Marble::QueueIndex(TAKEN * period + t - 7)
+        } else if period == 0 {
+            Marble::Value(self.first_taken[t - 1])
+        } else if t % 2 == 1 {
+            Marble::QueueIndex(TAKEN * (period - 1) + 13 + t / 2)
+        } else {
+            Marble::Value(Queue::previous_special(period - 1) + 19 + t / 2)
+        }
+    }
+
+    fn get(&self, mut index: usize) -> usize {
+        loop {
+            if index < self.start.len() {
+                return self.start[index];
+            }
+            let period = (index - self.start.len()) / APPENDED;
+            let offset = (index - self.start.len()) % APPENDED;
+            let marble = if offset == 0 {
+                if period == 0 {
+                    Marble::Value(self.first_current)
+                } else {
+                    Marble::Value(Queue::previous_special(period - 1) + 19)
+                }
+            } else if offset.is_multiple_of(2) {
+                Marble::Value(Queue::previous_special(period) + offset / 2)
+            } else {
+                self.taken(period, offset.div_ceil(2))
+            };
+            match marble {
+                Marble::Value(value) => return value,
+                Marble::QueueIndex(earlier) => index = earlier,
+            }
+        }
+    }
+
+    // the marble removed in the given period (p19)
+    fn removed(&self, period: usize) -> usize {
+        match self.taken(period, 19) {
+            Marble::Value(value) => value,
+            Marble::QueueIndex(index) => self.get(index),
+        }
+    }
+}
+
+// the points of each player for the standard rules, the periods are split between threads
+pub fn periodic_scores(n_players: usize, n_marbles: usize) -> Vec<usize> {
+    let mut state: State<RotatingCircle<usize>> =
+        State::new(n_players, WARMUP_MARBLES, Rules::default());
+    while state.next_marble < n_marbles.min(WARMUP_MARBLES) {
+        state = state.turn().0;
+    }
+    if n_marbles <= WARMUP_MARBLES {
+        return state.points;
+    }
+
+    let circle: Vec<usize> = (0..state.circle.len())
+        .filter_map(|offset| state.circle.get(offset).copied())
+        .collect();
+    let queue = Queue {
+        first_current: circle[0],
+        first_taken: circle[1..7].to_vec(),
+        start: circle[7..].to_vec(),
+    };
+
+    let n_periods = (n_marbles - WARMUP_MARBLES) / PERIOD;
+    let n_threads = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk_size = n_periods.div_ceil(n_threads).max(1);
+    let mut points = state.points;
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..n_periods)
+            .step_by(chunk_size)
+            .map(|first| {
+                let queue = &queue;
+                scope.spawn(move || {
+                    let mut points = vec![0; n_players];
+                    for period in first..(first + chunk_size).min(n_periods) {
+                        let marble = Queue::previous_special(period) + PERIOD;
+                        points[(marble - 1) % n_players] += marble + queue.removed(period);
+                    }
+                    points
+                })
+            })
+            .collect();
+        for handle in handles {
+            let chunk_points = handle.join().expect("scoring thread does not panic");
+            for (total, chunk) in points.iter_mut().zip(chunk_points) {
+                *total += chunk;
+            }
+        }
+    });
+    points
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::play_game;
+    use super::*;
+
+    #[test]
+    fn periodic_scores_match_simulation() {
+        for n_players in [1, 9, 10, 13, 430] {
+            for n_marbles in (1..200).chain([1619, 7999, 25000]) {
+                assert_eq!(
+                    periodic_scores(n_players, n_marbles),
+                    play_game::<RotatingCircle<usize>>(
+                        n_players,
+                        n_marbles,
+                        Rules::default(),
+                        &mut |_| ()
+                    ),
+                    "{} players, {} marbles",
+                    n_players,
+                    n_marbles
+                );
+            }
+        }
+    }
+}
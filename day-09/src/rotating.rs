@@ -0,0 +1,136 @@
+use super::circle::Circle;
+use std::collections::VecDeque;
+
+// a circle in a ring buffer, the current element is always at the front. Moving the current
+// position rotates the buffer, which takes O(min(k, n - k)) for k steps. That is cheap for the
+// short moves of the marble game, but not for long jumps, IndexedCircle handles those in
+// constant time. Indexed access relative to the current element and inserting next to it take
+// constant time.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RotatingCircle<T> {
+    elements: VecDeque<T>,
+}
+
+impl<T> RotatingCircle<T> {
+    pub fn with_capacity(capacity: usize, first_element: T) -> RotatingCircle<T> {
+        let mut elements = VecDeque::with_capacity(capacity);
+        elements.push_back(first_element);
+        RotatingCircle { elements }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    // the element `offset` steps right of the current one, 0 is the current element
+    pub fn get(&self, offset: usize) -> Option<&T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.elements.get(offset % self.len())
+    }
+
+    pub fn rotate_right(&mut self, n: usize) {
+        if !self.is_empty() {
+            let n = n % self.len();
+            self.elements.rotate_left(n);
+        }
+    }
+
+    pub fn rotate_left(&mut self, n: usize) {
+        if !self.is_empty() {
+            let n = n % self.len();
+            self.elements.rotate_right(n);
+        }
+    }
+
+    // insert a value right of the current element
+    pub fn insert_right(&mut self, value: T) {
+        if self.is_empty() {
+            self.elements.push_back(value);
+        } else {
+            self.elements.insert(1, value);
+        }
+    }
+
+    // remove the current element and use the element to the right as new current element
+    pub fn remove_use_right(&mut self) -> Option<T> {
+        self.elements.pop_front()
+    }
+}
+
+impl<T> Circle<T> for RotatingCircle<T> {
+    fn with_capacity(capacity: usize, first_element: T) -> Self {
+        RotatingCircle::with_capacity(capacity, first_element)
+    }
+
+    fn len(&self) -> usize {
+        RotatingCircle::len(self)
+    }
+
+    fn move_right_n(&mut self, n: usize) {
+        self.rotate_right(n);
+    }
+
+    fn move_left_n(&mut self, n: usize) {
+        self.rotate_left(n);
+    }
+
+    fn insert_right(&mut self, value: T) {
+        RotatingCircle::insert_right(self, value);
+    }
+
+    fn remove_use_right(&mut self) -> Option<T> {
+        RotatingCircle::remove_use_right(self)
+    }
+
+    fn get(&self, offset: usize) -> Option<&T> {
+        RotatingCircle::get(self, offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rotations_and_indexed_access_work() {
+        // given
+        let mut circle: RotatingCircle<u32> = RotatingCircle::with_capacity(10, 0);
+        for value in (1..5).rev() {
+            circle.insert_right(value);
+        }
+
+        // when/then
+        assert_eq!(circle.get(2), Some(&2));
+        assert_eq!(circle.get(7), Some(&2));
+        circle.rotate_right(3);
+        assert_eq!(circle.get(0), Some(&3));
+        circle.rotate_left(1_000_000_001);
+        assert_eq!(circle.get(0), Some(&2));
+        circle.rotate_left(2);
+        assert_eq!(circle.get(0), Some(&0));
+    }
+
+    #[test]
+    fn remove_use_right_empties_the_circle() {
+        // given
+        let mut circle: RotatingCircle<u32> = RotatingCircle::with_capacity(2, 1);
+        circle.insert_right(2);
+
+        // when/then
+        assert_eq!(circle.remove_use_right(), Some(1));
+        assert_eq!(circle.get(0), Some(&2));
+        assert_eq!(circle.remove_use_right(), Some(2));
+        assert!(circle.is_empty());
+        assert_eq!(circle.get(0), None);
+        assert_eq!(circle.remove_use_right(), None);
+        circle.rotate_left(3);
+        circle.insert_right(3);
+        assert_eq!(circle.get(0), Some(&3));
+    }
+}
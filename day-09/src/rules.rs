@@ -0,0 +1,88 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rules {
+    // marbles that are a multiple of this are not placed but scored, at least 2 so that the
+    // circle never becomes empty
+    pub special_marble: usize,
+    // a new marble is placed right of the marble this many steps right of the current one
+    pub insertion_step: usize,
+    // a scoring player also gets the marble this many steps left of the current one
+    pub removal_offset: usize,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            special_marble: 23,
+            insertion_step: 1,
+            removal_offset: 7,
+        }
+    }
+}
+
+impl Rules {
+    // change one of the rules with an option like "special=19", "step=2" or "removal=5"
+    pub fn set_option(&mut self, option: &str) -> Result<(), String> {
+        let (key, value) = option
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid option '{}', expected key=value", option))?;
+        let value: usize = value
+            .parse()
+            .map_err(|_| format!("Invalid number in option '{}'", option))?;
+        match key {
+            // every scoring turn removes a marble, with special marbles of 2 or more, the turns
+            // in between place at least as many marbles
+            "special" if value < 2 => {
+                return Err(format!(
+                    "Invalid option '{}', the circle would become empty",
+                    option
+                ))
+            }
+            "special" => self.special_marble = value,
+            "step" => self.insertion_step = value,
+            "removal" => self.removal_offset = value,
+            _ => return Err(format!("Unknown rule '{}'", key)),
+        }
+        Ok(())
+    }
+}
+
+// what happened in one turn. Players are counted from 0, points is 0 for normal turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Event {
+    pub turn: usize,
+    pub player: usize,
+    pub marble: usize,
+    pub points: usize,
+    pub removed: Option<usize>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_option_changes_rules() {
+        // given
+        let mut rules = Rules::default();
+
+        // when
+        rules.set_option("special=19").unwrap();
+        rules.set_option("removal=5").unwrap();
+
+        // then
+        assert_eq!(
+            rules,
+            Rules {
+                special_marble: 19,
+                insertion_step: 1,
+                removal_offset: 5,
+            }
+        );
+        assert!(rules.set_option("step").is_err());
+        assert!(rules.set_option("jump=3").is_err());
+        assert!(rules.set_option("step=-1").is_err());
+        assert!(rules.set_option("special=1").is_err());
+        assert!(rules.set_option("special=0").is_err());
+        assert_eq!(rules.special_marble, 19);
+    }
+}
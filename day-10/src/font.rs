@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+
+// the letters of the messages are 6 points wide and 10 points high, with 2 empty columns
+// between them
+pub const GLYPH_WIDTH: i64 = 6;
+pub const GLYPH_HEIGHT: i64 = 10;
+pub const GLYPH_SPACING: i64 = 2;
+
+// all letters that are known to appear in messages
+const GLYPHS: [(char, [&str; 10]); 16] = [
+    (
+        'A',
+        [
+            "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'B',
+        [
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+            "#....#", "#####.",
+        ],
+    ),
+    (
+        'C',
+        [
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#....#", ".####.",
+        ],
+    ),
+    (
+        'E',
+        [
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+    (
+        'F',
+        [
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    ),
+    (
+        'G',
+        [
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+            "#...##", ".###.#",
+        ],
+    ),
+    (
+        'H',
+        [
+            "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'J',
+        [
+            "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+            "#...#.", ".###..",
+        ],
+    ),
+    (
+        'K',
+        [
+            "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+            "#...#.", "#....#",
+        ],
+    ),
+    (
+        'L',
+        [
+            "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+    (
+        'N',
+        [
+            "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+            "#...##", "#....#",
+        ],
+    ),
+    (
+        'P',
+        [
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    ),
+    (
+        'R',
+        [
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'X',
+        [
+            "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'Z',
+        [
+            "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+    (
+        ' ',
+        [
+            "......", "......", "......", "......", "......", "......", "......", "......",
+            "......", "......",
+        ],
+    ),
+];
+
+// read the letters from the lit positions, the top left corner of the first letter is at
+// (left, top). Unknown letters are returned as '?'.
+pub fn recognize(lit: &HashSet<(i64, i64)>, left: i64, top: i64, n_letters: usize) -> String {
+    (0..n_letters as i64)
+        .map(|index| {
+            let letter_left = left + index * (GLYPH_WIDTH + GLYPH_SPACING);
+            GLYPHS
+                .iter()
+                .find(|(_, rows)| {
+                    rows.iter().zip(top..).all(|(row, y)| {
+                        row.chars()
+                            .zip(letter_left..)
+                            .all(|(c, x)| (c == '#') == lit.contains(&(x, y)))
+                    })
+                })
+                .map(|(letter, _)| *letter)
+                .unwrap_or('?')
+        })
+        .collect()
+}
+
+// the lit positions of a text, the top left corner of the first letter is at (0, 0)
+pub fn render_text(text: &str) -> Result<Vec<(i64, i64)>, String> {
+    let mut lit: Vec<(i64, i64)> = Vec::new();
+    for (index, letter) in text.chars().enumerate() {
+        let letter = letter.to_ascii_uppercase();
+        let (_, rows) = GLYPHS
+            .iter()
+            .find(|(c, _)| *c == letter)
+            .ok_or_else(|| format!("No glyph for letter '{}'", letter))?;
+        let letter_left = index as i64 * (GLYPH_WIDTH + GLYPH_SPACING);
+        for (row, y) in rows.iter().zip(0..) {
+            for (c, x) in row.chars().zip(letter_left..) {
+                if c == '#' {
+                    lit.push((x, y));
+                }
+            }
+        }
+    }
+    Ok(lit)
+}
+
+// the number of letters in a message of the given width
+pub fn message_length(width: i64) -> usize {
+    ((width + GLYPH_SPACING) / (GLYPH_WIDTH + GLYPH_SPACING)).max(0) as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recognize_reads_known_letters() {
+        // given
+        let lit: HashSet<(i64, i64)> = render_text("hZa")
+            .unwrap()
+            .into_iter()
+            .map(|(x, y)| (x + 10, y - 3))
+            .collect();
+        let mut damaged = lit.clone();
+        damaged.remove(&(10, -3));
+
+        // when
+        let message = recognize(&lit, 10, -3, message_length(22));
+        let damaged_message = recognize(&damaged, 10, -3, 3);
+
+        // then
+        assert_eq!(message, "HZA");
+        assert_eq!(damaged_message, "?ZA");
+        assert!(render_text("HQ").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use super::font::render_text;
+use super::Point;
+
+// xorshift64*, good enough to scatter some points
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        // xorshift never leaves a state of 0, so the one seed that would lead to it gets the
+        // state of seed 0 instead
+        const SCRAMBLE: u64 = 0x9E37_79B9_7F4A_7C15;
+        match seed ^ SCRAMBLE {
+            0 => Random(SCRAMBLE),
+            state => Random(state),
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // a number from -max to max
+    fn next_in_range(&mut self, max: i32) -> i32 {
+        (self.next() % (2 * max as u64 + 1)) as i32 - max
+    }
+}
+
+// points that spell the text after the given number of seconds, each point has a random
+// velocity of at most max_speed in each direction (but never stands still)
+pub fn generate(
+    text: &str,
+    seconds: i32,
+    max_speed: i32,
+    random: &mut Random,
+) -> Result<Vec<Point>, String> {
+    if max_speed < 1 {
+        return Err("The maximum speed must be at least 1".to_owned());
+    }
+    // spaces have no lit points, so spaces at the ends would not be part of the message
+    if text.trim() != text || text.is_empty() {
+        return Err(format!(
+            "The text '{}' must not be empty or start or end with a space",
+            text
+        ));
+    }
+    render_text(text)?
+        .into_iter()
+        .map(|(x, y)| {
+            let velocity = loop {
+                let velocity = (
+                    random.next_in_range(max_speed),
+                    random.next_in_range(max_speed),
+                );
+                if velocity != (0, 0) {
+                    break velocity;
+                }
+            };
+            let rewind = |coordinate: i64, speed: i32| {
+                i32::try_from(coordinate - speed as i64 * seconds as i64)
+                    .map_err(|_| format!("Rewinding by {} seconds is too far", seconds))
+            };
+            Ok(Point {
+                position: (rewind(x, velocity.0)?, rewind(y, velocity.1)?),
+                velocity,
+            })
+        })
+        .collect()
+}
+
+// the same format as the puzzle input
+pub fn format_point(point: &Point) -> String {
+    format!(
+        "position=<{:>6}, {:>6}> velocity=<{:>2}, {:>2}>",
+        point.position.0, point.position.1, point.velocity.0, point.velocity.1
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::font::{message_length, recognize};
+    use super::super::{convergence_time, get_bounds, parse_line, positions_at};
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn generated_points_can_be_parsed_and_converge() {
+        // given
+        let mut random = Random::new(42);
+
+        // when
+        let points = generate("BLAZE", 10_000, 5, &mut random).unwrap();
+
+        // then
+        let lines: Vec<String> = points.iter().map(format_point).collect();
+        let parsed: Vec<Point> = lines.iter().filter_map(|l| parse_line(l)).collect();
+        assert_eq!(parsed, points);
+        assert_eq!(convergence_time(&parsed), 10_000);
+        assert!(generate("BLAZE", i32::MAX, 5, &mut random).is_err());
+    }
+
+    #[test]
+    fn generated_text_is_recognized() {
+        // given
+        let mut random = Random::new(7);
+        let points = generate("HAZE BLAZE", 500, 3, &mut random).unwrap();
+
+        // when
+        let positions = positions_at(&points, convergence_time(&points));
+        let (lower_x, lower_y, upper_x, _) = get_bounds(&positions);
+        let lit: HashSet<(i64, i64)> = positions.into_iter().collect();
+        let message = recognize(
+            &lit,
+            lower_x,
+            lower_y,
+            message_length(upper_x - lower_x + 1),
+        );
+
+        // then
+        assert_eq!(message, "HAZE BLAZE");
+        assert!(generate(" HAZE", 500, 3, &mut random).is_err());
+        assert!(generate("HAZE ", 500, 3, &mut random).is_err());
+        assert!(generate("", 500, 3, &mut random).is_err());
+    }
+
+    #[test]
+    fn random_never_gets_stuck_at_zero() {
+        // given
+        let mut random = Random::new(0x9E37_79B9_7F4A_7C15);
+
+        // when
+        let numbers: Vec<u64> = (0..3).map(|_| random.next()).collect();
+
+        // then
+        assert!(numbers.iter().all(|n| *n != 0), "{:?}", numbers);
+    }
+}
@@ -1,64 +1,133 @@
+use std::collections::HashSet;
 use std::env;
 use std::fs::read_to_string;
 use std::path::Path;
 
+mod font;
+mod generate;
+
+use self::font::{message_length, recognize, GLYPH_HEIGHT};
+use self::generate::{format_point, generate, Random};
+use std::time::{SystemTime, UNIX_EPOCH};
+
 fn main() -> Result<(), String> {
+    // "generate TEXT SECONDS [SEED]" prints points that show the text after that many seconds
+    if env::args().nth(1).as_deref() == Some("generate") {
+        let text = env::args().nth(2).ok_or("No text given.".to_owned())?;
+        let seconds: i32 = env::args()
+            .nth(3)
+            .and_then(|s| s.parse().ok())
+            .ok_or("No valid number of seconds given.".to_owned())?;
+        let seed: u64 = match env::args().nth(4) {
+            Some(seed) => seed.parse().map_err(|_| "Invalid seed.".to_owned())?,
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0),
+        };
+        for point in generate(&text, seconds, 5, &mut Random::new(seed))? {
+            println!("{}", format_point(&point));
+        }
+        return Ok(());
+    }
+
     let filename = env::args().nth(1).ok_or("No file name given.".to_owned())?;
     let content = read_to_string(Path::new(&filename)).map_err(|e| e.to_string())?;
-    let mut points = parse_input(&content);
+    let points = parse_input(&content);
     if points.is_empty() {
         return Err("Expected points.".to_owned());
     }
 
-    let mut timer = 0;
-    while height(&points) > 20 {
-        move_points(&mut points);
-        timer += 1;
-    }
-    while height(&points) <= 20 {
-        println!("-----------time: {}----------------", timer);
-        print_points(&points);
-        move_points(&mut points);
-        timer += 1;
+    let time = convergence_time(&points);
+    let positions = positions_at(&points, time);
+    let (lower_x, lower_y, upper_x, upper_y) = get_bounds(&positions);
+    let message = if upper_y - lower_y + 1 == GLYPH_HEIGHT {
+        let lit: HashSet<(i64, i64)> = positions.iter().copied().collect();
+        recognize(
+            &lit,
+            lower_x,
+            lower_y,
+            message_length(upper_x - lower_x + 1),
+        )
+    } else {
+        String::new()
+    };
+
+    if message.is_empty() || message.contains('?') {
+        println!("The points are closest together after {} seconds:", time);
+        print!("{}", render(&positions));
+        if !message.is_empty() {
+            println!("The message looks like '{}'", message);
+        }
+    } else {
+        println!("The message '{}' appears after {} seconds", message, time);
     }
 
     Ok(())
 }
 
-fn height(points: &[Point]) -> i32 {
-    let (_, lower_y, _, upper_y) = get_bounds(points);
-    upper_y - lower_y + 1
+fn positions_at(points: &[Point], time: i64) -> Vec<(i64, i64)> {
+    points
+        .iter()
+        .map(|p| {
+            (
+                p.position.0 as i64 + p.velocity.0 as i64 * time,
+                p.position.1 as i64 + p.velocity.1 as i64 * time,
+            )
+        })
+        .collect()
+}
+
+// width plus height of the bounding box. Both are the difference of a maximum and a minimum of
+// linear functions of the time, so this is a convex function of the time.
+fn extent(points: &[Point], time: i64) -> i64 {
+    let (lower_x, lower_y, upper_x, upper_y) = get_bounds(&positions_at(points, time));
+    (upper_x - lower_x) + (upper_y - lower_y)
 }
 
-fn move_points(points: &mut [Point]) {
-    for p in points {
-        p.position.0 += p.velocity.0;
-        p.position.1 += p.velocity.1;
+// the first (non-negative) time with the smallest bounding box
+fn convergence_time(points: &[Point]) -> i64 {
+    // find an upper bound, the extent grows after the minimum
+    let mut upper: i64 = 1;
+    while extent(points, upper * 2) < extent(points, upper) {
+        upper *= 2;
     }
+    upper *= 2;
+
+    // ternary search, the function is convex, so the only plateau is at the minimum
+    let mut lower: i64 = 0;
+    while upper - lower > 2 {
+        let left = lower + (upper - lower) / 3;
+        let right = upper - (upper - lower) / 3;
+        if extent(points, left) <= extent(points, right) {
+            upper = right;
+        } else {
+            lower = left;
+        }
+    }
+    (lower..=upper)
+        .min_by_key(|time| (extent(points, *time), *time))
+        .unwrap_or(lower)
 }
 
-fn print_points(points: &[Point]) {
-    let (lower_x, lower_y, upper_x, upper_y) = get_bounds(points);
+fn render(positions: &[(i64, i64)]) -> String {
+    let (lower_x, lower_y, upper_x, upper_y) = get_bounds(positions);
+    let lit: HashSet<(i64, i64)> = positions.iter().copied().collect();
+    let mut output = String::new();
     for y in lower_y..(upper_y + 1) {
         for x in lower_x..(upper_x + 1) {
-            if points
-                .iter()
-                .any(|p| p.position.0 == x && p.position.1 == y)
-            {
-                print!("#");
-            } else {
-                print!(".");
-            }
+            output.push(if lit.contains(&(x, y)) { '#' } else { '.' });
         }
-        println!();
+        output.push('\n');
     }
+    output
 }
 
-fn get_bounds(points: &[Point]) -> (i32, i32, i32, i32) {
-    let lower_x = points.iter().map(|p| p.position.0).min().unwrap();
-    let lower_y = points.iter().map(|p| p.position.1).min().unwrap();
-    let upper_x = points.iter().map(|p| p.position.0).max().unwrap();
-    let upper_y = points.iter().map(|p| p.position.1).max().unwrap();
+fn get_bounds(positions: &[(i64, i64)]) -> (i64, i64, i64, i64) {
+    let lower_x = positions.iter().map(|p| p.0).min().unwrap();
+    let lower_y = positions.iter().map(|p| p.1).min().unwrap();
+    let upper_x = positions.iter().map(|p| p.0).max().unwrap();
+    let upper_y = positions.iter().map(|p| p.1).max().unwrap();
 
     (lower_x, lower_y, upper_x, upper_y)
 }
@@ -106,4 +175,80 @@ mod test {
         assert_eq!(p.position, (-20620, -41485));
         assert_eq!(p.velocity, (2, 4));
     }
+
+    const EXAMPLE: &str = "position=< 9,  1> velocity=< 0,  2>
+position=< 7,  0> velocity=<-1,  0>
+position=< 3, -2> velocity=<-1,  1>
+position=< 6, 10> velocity=<-2, -1>
+position=< 2, -4> velocity=< 2,  2>
+position=<-6, 10> velocity=< 2, -2>
+position=< 1,  8> velocity=< 1, -1>
+position=< 1,  7> velocity=< 1,  0>
+position=<-3, 11> velocity=< 1, -2>
+position=< 7,  6> velocity=<-1, -1>
+position=<-2,  3> velocity=< 1,  0>
+position=<-4,  3> velocity=< 2,  0>
+position=<10, -3> velocity=<-1,  1>
+position=< 5, 11> velocity=< 1, -2>
+position=< 4,  7> velocity=< 0, -1>
+position=< 8, -2> velocity=< 0,  1>
+position=<15,  0> velocity=<-2,  0>
+position=< 1,  6> velocity=< 1,  0>
+position=< 8,  9> velocity=< 0, -1>
+position=< 3,  3> velocity=<-1,  1>
+position=< 0,  5> velocity=< 0, -1>
+position=<-2,  2> velocity=< 2,  0>
+position=< 5, -2> velocity=< 1,  2>
+position=< 1,  4> velocity=< 2,  1>
+position=<-2,  7> velocity=< 2, -2>
+position=< 3,  6> velocity=<-1, -1>
+position=< 5,  0> velocity=< 1,  0>
+position=<-6,  0> velocity=< 2,  0>
+position=< 5,  9> velocity=< 1, -2>
+position=<14,  7> velocity=<-2,  0>
+position=<-3,  6> velocity=< 2, -1>
+";
+
+    #[test]
+    fn convergence_time_works_for_example() {
+        // given
+        let points = parse_input(EXAMPLE);
+
+        // when
+        let time = convergence_time(&points);
+
+        // then
+        assert_eq!(time, 3);
+        assert_eq!(
+            render(&positions_at(&points, time)),
+            "#...#..###
+#...#...#.
+#...#...#.
+#####...#.
+#...#...#.
+#...#...#.
+#...#...#.
+#...#..###
+"
+        );
+    }
+
+    #[test]
+    fn convergence_time_finds_late_minimum() {
+        // given
+        // two points that meet after 12345 seconds
+        let points = vec![
+            Point {
+                position: (-12345, 0),
+                velocity: (1, 0),
+            },
+            Point {
+                position: (24690, 1),
+                velocity: (-2, 0),
+            },
+        ];
+
+        // when/then
+        assert_eq!(convergence_time(&points), 12345);
+    }
 }
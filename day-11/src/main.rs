@@ -1,104 +1,108 @@
-static GRID_SIDE: i32 = 300;
+use std::cmp::Reverse;
+use std::env;
+use std::thread;
+
+mod summed_area;
+
+use self::summed_area::SummedAreaTable;
 
 fn main() -> Result<(), String> {
-    let puzzle_input: i32 = 5177;
+    // the serial number and the grid size can be given as arguments
+    let puzzle_input: i32 = match env::args().nth(1) {
+        Some(serial) => serial.parse().map_err(|_| "Invalid serial number.")?,
+        None => 5177,
+    };
+    let grid_side: usize = match env::args().nth(2) {
+        Some(side) => side.parse().map_err(|_| "Invalid grid size.")?,
+        None => 300,
+    };
 
-    let grid = power_grid(puzzle_input);
-    let cumsum = cumsum_grid(&grid);
-    let (xmax, ymax) = max_3_square(&cumsum);
+    let grid = power_grid(grid_side, |x, y| power_level(x, y, puzzle_input));
+    let (xmax, ymax) = max_3_square(&grid).ok_or("The grid is smaller than 3×3.")?;
 
     println!(
         "Position of highest power of a 3×3 square: {}×{}",
         xmax, ymax
     );
 
-    let (x, y, side) = max_square(&cumsum);
+    let per_side = best_squares(&grid);
+    let (x, y, side) = max_square(&per_side).ok_or("The grid is empty.")?;
     println!(
         "Square with the highest power: x: {}, y: {}, side: {}",
         x, y, side
     );
 
-    Ok(())
-}
-
-fn empty_grid() -> Vec<i32> {
-    (0..(GRID_SIDE * GRID_SIDE)).map(|_| 0).collect()
-}
-
-fn power_grid(serial: i32) -> Vec<i32> {
-    let mut grid = empty_grid();
-    for y in 1..GRID_SIDE + 1 {
-        for x in 1..(GRID_SIDE + 1) {
-            grid[index(x, y)] = power_level(x, y, serial);
+    // the best square of each side, to see how the power develops with the size
+    if env::args().nth(3).as_deref() == Some("curve") {
+        println!("side,x,y,power");
+        for (side, (x, y, power)) in per_side.iter().enumerate() {
+            println!("{},{},{},{}", side + 1, x, y, power);
         }
     }
-    return grid;
+
+    Ok(())
 }
 
-fn cumsum_grid(grid: &[i32]) -> Vec<i32> {
-    let mut cumsum = grid.to_vec();
-    for y in 1..GRID_SIDE {
-        for x in 1..GRID_SIDE {
-            let index = (x + y * GRID_SIDE) as usize;
-            let index_left = (x - 1 + y * GRID_SIDE) as usize;
-            let index_upper = (x + (y - 1) * GRID_SIDE) as usize;
-            let index_remove = (x - 1 + (y - 1) * GRID_SIDE) as usize;
-            cumsum[index] =
-                cumsum[index] + cumsum[index_left] + cumsum[index_upper] - cumsum[index_remove];
-        }
-    }
-    return cumsum;
+// a square grid, power gets the 1-based x and y coordinates of a cell
+fn power_grid<F: Fn(i32, i32) -> i32>(side: usize, power: F) -> SummedAreaTable<i32> {
+    SummedAreaTable::new(side, side, |x, y| power(x as i32 + 1, y as i32 + 1))
 }
 
-fn max_square(cumsum: &[i32]) -> (i32, i32, i32) {
-    let mut square: (i32, i32, i32) = (1, 1, 1);
-    let mut max: i32 = i32::min_value();
-    for x in 1..GRID_SIDE {
-        for y in 1..GRID_SIDE {
-            let max_side = std::cmp::min(GRID_SIDE - x, GRID_SIDE - y);
-            for side in 1..max_side {
-                let power = area_value(cumsum, x, y, side, side);
-                if power > max {
-                    max = power;
-                    square = (x, y, side + 1);
-                }
+// the top left corner and the power of the square with the given side with the highest power
+fn best_square_of_side(grid: &SummedAreaTable<i32>, side: usize) -> Option<(i32, i32, i32)> {
+    let mut best: Option<(i32, i32, i32)> = None;
+    for y in 0..(grid.height() + 1).saturating_sub(side) {
+        for x in 0..(grid.width() + 1).saturating_sub(side) {
+            let power = grid.sum(x, y, side, side)?;
+            if best.map(|(_, _, max)| power > max).unwrap_or(true) {
+                best = Some((x as i32 + 1, y as i32 + 1, power));
             }
         }
     }
-    return square;
+    best
 }
 
-fn max_3_square(cumsum: &[i32]) -> (i32, i32) {
-    let mut max: i32 = i32::min_value();
-    let mut pos: (i32, i32) = (1, 1);
-    for x in 1..GRID_SIDE - 1 {
-        for y in 1..(GRID_SIDE - 1) {
-            let power = area_value(cumsum, x, y, 2, 2);
-            if power > max {
-                max = power;
-                pos = (x, y);
+// the best square for each side from 1 to the grid size, as (x, y, power). The sides are
+// distributed over all available threads. Small squares have more positions, so each thread takes
+// every n-th side to get about the same amount of work.
+fn best_squares(grid: &SummedAreaTable<i32>) -> Vec<(i32, i32, i32)> {
+    let max_side = grid.width().min(grid.height());
+    let n_threads = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(max_side.max(1));
+    let mut per_side: Vec<(i32, i32, i32)> = vec![(0, 0, 0); max_side];
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..n_threads)
+            .map(|first| {
+                scope.spawn(move || {
+                    (first + 1..=max_side)
+                        .step_by(n_threads)
+                        .filter_map(|side| best_square_of_side(grid, side).map(|best| (side, best)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        for handle in handles {
+            for (side, best) in handle.join().expect("search thread does not panic") {
+                per_side[side - 1] = best;
             }
         }
-    }
-    return pos;
+    });
+    per_side
 }
 
-fn area_value(cumsum: &[i32], x: i32, y: i32, xs: i32, ys: i32) -> i32 {
-    let mut value = cumsum[index(x + xs, y + ys)];
-    if x > 1 {
-        value -= cumsum[index(x - 1, y + ys)];
-    }
-    if y > 1 {
-        value -= cumsum[index(x + xs, y - 1)];
-    }
-    if x > 1 && y > 1 {
-        value += cumsum[index(x - 1, y - 1)];
-    }
-    return value;
+// the overall best square as (x, y, side), the smallest side wins a tie
+fn max_square(per_side: &[(i32, i32, i32)]) -> Option<(i32, i32, i32)> {
+    per_side
+        .iter()
+        .zip(1..)
+        .max_by_key(|((_, _, power), side)| (*power, Reverse(*side)))
+        .map(|((x, y, _), side)| (*x, *y, side))
 }
 
-fn index(x: i32, y: i32) -> usize {
-    ((x - 1) + (y - 1) * GRID_SIDE) as usize
+fn max_3_square(grid: &SummedAreaTable<i32>) -> Option<(i32, i32)> {
+    best_square_of_side(grid, 3).map(|(x, y, _)| (x, y))
 }
 
 fn power_level(x: i32, y: i32, serial: i32) -> i32 {
@@ -118,4 +122,47 @@ mod test {
         assert_eq!(power_level(217, 196, 39), 0);
         assert_eq!(power_level(101, 153, 71), 4);
     }
+
+    #[test]
+    fn max_3_square_works_for_examples() {
+        let grid = power_grid(300, |x, y| power_level(x, y, 18));
+        assert_eq!(best_square_of_side(&grid, 3), Some((33, 45, 29)));
+        let grid = power_grid(300, |x, y| power_level(x, y, 42));
+        assert_eq!(max_3_square(&grid), Some((21, 61)));
+    }
+
+    #[test]
+    fn best_squares_match_brute_force() {
+        // given
+        let grid = power_grid(12, |x, y| (x * 7 + y * 3) % 10 - 5);
+
+        // when
+        let per_side = best_squares(&grid);
+
+        // then
+        for side in 1..=12 {
+            let mut best = i32::MIN;
+            for y in 1..=(13 - side) {
+                for x in 1..=(13 - side) {
+                    let power: i32 = (x..x + side)
+                        .flat_map(|cx| (y..y + side).map(move |cy| (cx * 7 + cy * 3) % 10 - 5))
+                        .sum();
+                    best = best.max(power);
+                }
+            }
+            assert_eq!(per_side[side as usize - 1].2, best);
+        }
+        assert_eq!(max_square(&[]), None);
+    }
+
+    #[test]
+    fn max_square_works_for_examples() {
+        let grid = power_grid(300, |x, y| power_level(x, y, 18));
+        let per_side = best_squares(&grid);
+        assert_eq!(per_side.len(), 300);
+        assert_eq!(per_side[15], (90, 269, 113));
+        assert_eq!(max_square(&per_side), Some((90, 269, 16)));
+        let grid = power_grid(300, |x, y| power_level(x, y, 42));
+        assert_eq!(max_square(&best_squares(&grid)), Some((232, 251, 12)));
+    }
 }
@@ -0,0 +1,88 @@
+use std::ops::{Add, Sub};
+
+// sums of all rectangles of a grid in constant time. sums[(x, y)] is the sum of all cells left
+// of x and above y, so the first row and column are zero.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SummedAreaTable<T> {
+    width: usize,
+    height: usize,
+    sums: Vec<T>,
+}
+
+impl<T> SummedAreaTable<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    // value gets the (0-based) x and y coordinate of a cell
+    pub fn new<F: Fn(usize, usize) -> T>(width: usize, height: usize, value: F) -> Self {
+        let stride = width + 1;
+        let mut sums = vec![T::default(); stride * (height + 1)];
+        for y in 0..height {
+            for x in 0..width {
+                sums[(x + 1) + (y + 1) * stride] =
+                    value(x, y) + sums[x + (y + 1) * stride] + sums[(x + 1) + y * stride]
+                        - sums[x + y * stride];
+            }
+        }
+        SummedAreaTable {
+            width,
+            height,
+            sums,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // the sum of the rectangle with the top left corner (x, y), None if the rectangle is not
+    // completely inside the grid
+    pub fn sum(&self, x: usize, y: usize, width: usize, height: usize) -> Option<T> {
+        if x + width > self.width || y + height > self.height {
+            return None;
+        }
+        Some(self.sum_unchecked(x, y, width, height))
+    }
+
+    fn sum_unchecked(&self, x: usize, y: usize, width: usize, height: usize) -> T {
+        let stride = self.width + 1;
+        let right = x + width;
+        let bottom = y + height;
+        self.sums[right + bottom * stride]
+            - self.sums[x + bottom * stride]
+            - self.sums[right + y * stride]
+            + self.sums[x + y * stride]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sum_matches_direct_sum() {
+        // given
+        let value = |x: usize, y: usize| (x as i64 * 7 + y as i64 * 13) % 11 - 5;
+        let table = SummedAreaTable::new(5, 4, value);
+
+        // when/then
+        for (x, y, width, height) in [(0, 0, 5, 4), (1, 2, 3, 2), (4, 3, 1, 1), (2, 1, 0, 3)] {
+            let expected: i64 = (x..x + width)
+                .flat_map(|cx| (y..y + height).map(move |cy| value(cx, cy)))
+                .sum();
+            assert_eq!(table.sum(x, y, width, height), Some(expected));
+        }
+        assert_eq!(table.sum(3, 0, 3, 1), None);
+        assert_eq!(table.sum(0, 4, 1, 1), None);
+    }
+
+    #[test]
+    fn sum_works_for_floats() {
+        let table = SummedAreaTable::new(2, 2, |x, y| 0.5 * (x + 2 * y) as f64);
+        assert_eq!(table.sum(0, 0, 2, 2), Some(3.0));
+    }
+}
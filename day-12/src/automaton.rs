@@ -0,0 +1,356 @@
+use super::cycle::{self, Generation};
+
+// the largest rule table we are willing to build
+const MAX_TABLE_LEN: usize = 1 << 24;
+
+// what happens to neighbourhoods that are not in a partial rule table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fallback {
+    // the cell keeps its state
+    Keep,
+    State(u8),
+}
+
+// a one-dimensional cellular automaton with cell states 0 to states - 1. The next state of a cell
+// depends on the cells up to radius positions left and right of it. State 0 is the background,
+// all cells outside of a row have this state.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Automaton {
+    states: u8,
+    radius: usize,
+    // the next state for each neighbourhood, indexed by the neighbourhood read as a number in
+    // base `states`, the leftmost cell is the most significant digit
+    table: Vec<u8>,
+}
+
+impl Automaton {
+    // the rule number in Wolfram's numbering, written in decimal: digit i of the number in
+    // base `states` is the next state for neighbourhood i. Rule 90 with 2 states and radius 1
+    // is the Sierpinski triangle.
+    pub fn from_rule_number(states: u8, radius: usize, number: &str) -> Result<Automaton, String> {
+        let table_len = table_len(states, radius)?;
+        let mut digits: Vec<u32> = number
+            .chars()
+            .map(|c| c.to_digit(10))
+            .collect::<Option<Vec<u32>>>()
+            .filter(|digits| !digits.is_empty())
+            .ok_or_else(|| format!("Invalid rule number '{}'", number))?;
+        let mut table: Vec<u8> = Vec::with_capacity(table_len);
+        for _ in 0..table_len {
+            // long division of the decimal digits by the number of states
+            let mut remainder: u32 = 0;
+            for digit in digits.iter_mut() {
+                let current = remainder * 10 + *digit;
+                *digit = current / states as u32;
+                remainder = current % states as u32;
+            }
+            table.push(remainder as u8);
+        }
+        if digits.iter().any(|digit| *digit != 0) {
+            return Err(format!(
+                "The rule number {} is too large for {} states and radius {}",
+                number, states, radius
+            ));
+        }
+        Ok(Automaton {
+            states,
+            radius,
+            table,
+        })
+    }
+
+    // neighbourhoods are given from left to right and must have 2 * radius + 1 cells
+    pub fn from_table(
+        states: u8,
+        radius: usize,
+        entries: &[(Vec<u8>, u8)],
+        fallback: Fallback,
+    ) -> Result<Automaton, String> {
+        let table_len = table_len(states, radius)?;
+        let table: Vec<u8> = (0..table_len)
+            .map(|index| match fallback {
+                Fallback::Keep => (index / (states as usize).pow(radius as u32)) as u8 % states,
+                Fallback::State(state) => state,
+            })
+            .collect();
+        let mut automaton = Automaton {
+            states,
+            radius,
+            table,
+        };
+        if let Fallback::State(state) = fallback {
+            automaton.check_state(state)?;
+        }
+        for (neighbourhood, next) in entries {
+            if neighbourhood.len() != 2 * radius + 1 {
+                return Err(format!(
+                    "Expected {} cells in neighbourhood {:?}",
+                    2 * radius + 1,
+                    neighbourhood
+                ));
+            }
+            automaton.check_state(*next)?;
+            let mut index: usize = 0;
+            for cell in neighbourhood {
+                automaton.check_state(*cell)?;
+                index = index * states as usize + *cell as usize;
+            }
+            automaton.table[index] = *next;
+        }
+        Ok(automaton)
+    }
+
+    fn check_state(&self, state: u8) -> Result<(), String> {
+        if state >= self.states {
+            return Err(format!(
+                "State {} is not one of the {} states",
+                state, self.states
+            ));
+        }
+        Ok(())
+    }
+
+    // an empty background must stay empty, or every row would be infinite
+    fn check_quiescent(&self) -> Result<(), String> {
+        if self.table[0] != 0 {
+            return Err(
+                "The rules change empty cells without neighbours, every row would be infinite"
+                    .to_owned(),
+            );
+        }
+        Ok(())
+    }
+
+    pub fn step(&self, row: &Row) -> Result<Row, String> {
+        self.check_quiescent()?;
+        let states = self.states as usize;
+        let radius = self.radius as i64;
+        let len = row.pattern.len as i64;
+        let mut cells: Vec<u8> = Vec::with_capacity(row.pattern.len + 2 * self.radius);
+        // the neighbourhood as a number, one cell is shifted in per step. All cells left of the
+        // row are background.
+        let mut neighbourhood: usize = 0;
+        let mut entering = row.pattern.cells();
+        for _ in -radius..(len + radius) {
+            let cell = entering.next().unwrap_or(0);
+            neighbourhood = (neighbourhood * states + cell as usize) % self.table.len();
+            cells.push(self.table[neighbourhood]);
+        }
+        Ok(Row::new(self.states, &cells, row.offset - radius))
+    }
+
+    // the row after the given number of generations. Once a pattern repeats, the later
+    // generations are calculated from the cycle instead of being simulated.
+    pub fn run(&self, row: &Row, generations: u64) -> Result<Row, String> {
+        self.check_quiescent()?;
+        cycle::run(row.clone(), generations, |row| self.step(row))
+    }
+}
+
+fn table_len(states: u8, radius: usize) -> Result<usize, String> {
+    if states < 2 {
+        return Err("An automaton needs at least 2 states".to_owned());
+    }
+    u32::try_from(2 * radius + 1)
+        .ok()
+        .and_then(|cells| (states as usize).checked_pow(cells))
+        .filter(|len| *len <= MAX_TABLE_LEN)
+        .ok_or_else(|| {
+            format!(
+                "The rule table for {} states and radius {} is too large",
+                states, radius
+            )
+        })
+}
+
+// the cells of a row without the background around it, each cell takes as many bits as
+// needed for the number of states. The packing keeps the patterns that are remembered to find
+// cycles small, a step still looks up the next state of every single cell.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    bits_per_cell: u32,
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl Pattern {
+    fn cells_per_word(&self) -> usize {
+        (u64::BITS / self.bits_per_cell) as usize
+    }
+
+    // the cells from left to right, unpacked one word after the other
+    fn cells(&self) -> impl Iterator<Item = u8> + '_ {
+        let bits_per_cell = self.bits_per_cell;
+        let mask: u64 = (1 << bits_per_cell) - 1;
+        let cells_per_word = self.cells_per_word() as u32;
+        self.words
+            .iter()
+            .flat_map(move |word| {
+                (0..cells_per_word).map(move |i| ((word >> (i * bits_per_cell)) & mask) as u8)
+            })
+            .take(self.len)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Row {
+    pattern: Pattern,
+    // the position of the first cell of the pattern
+    offset: i64,
+}
+
+impl Row {
+    // the first cell is at position offset, background cells at both ends are dropped
+    pub fn new(states: u8, cells: &[u8], offset: i64) -> Row {
+        let first = cells.iter().position(|cell| *cell != 0);
+        let last = cells.iter().rposition(|cell| *cell != 0);
+        let (cells, offset) = match (first, last) {
+            (Some(first), Some(last)) => (&cells[first..=last], offset + first as i64),
+            _ => (&cells[0..0], 0),
+        };
+        let bits_per_cell = u8::BITS - (states.max(2) - 1).leading_zeros();
+        let cells_per_word = (u64::BITS / bits_per_cell) as usize;
+        let words: Vec<u64> = cells
+            .chunks(cells_per_word)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| (*cell as u64) << (i as u32 * bits_per_cell))
+                    .fold(0, |word, cell| word | cell)
+            })
+            .collect();
+        Row {
+            pattern: Pattern {
+                bits_per_cell,
+                len: cells.len(),
+                words,
+            },
+            offset,
+        }
+    }
+
+    // the positions and states of all cells that are not background
+    pub fn cells(&self) -> impl Iterator<Item = (i64, u8)> + '_ {
+        (self.offset..)
+            .zip(self.pattern.cells())
+            .filter(|(_, state)| *state != 0)
+    }
+
+    pub fn sum_of_positions(&self) -> i64 {
+        self.cells().map(|(position, _)| position).sum()
+    }
+
+    // symbols[state] for each cell from the first to the last cell that is not background
+    pub fn render(&self, symbols: &[char]) -> String {
+        self.pattern
+            .cells()
+            .map(|state| symbols.get(state as usize).copied().unwrap_or('?'))
+            .collect()
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+}
+
+impl Generation for Row {
+    type Pattern = Pattern;
+
+    fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    fn with_offset(self, offset: i64) -> Row {
+        Row { offset, ..self }
+    }
+
+    fn size(&self) -> usize {
+        self.pattern.len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rule_90_draws_sierpinski_triangle() {
+        // given
+        let automaton = Automaton::from_rule_number(2, 1, "90").unwrap();
+        let row = Row::new(2, &[0, 1, 0], 0);
+
+        // when
+        let after_one = automaton.run(&row, 1).unwrap();
+        let after_three = automaton.run(&row, 3).unwrap();
+
+        // then
+        assert_eq!(after_one.render(&['.', '#']), "#.#");
+        assert_eq!(after_one.offset(), 0);
+        assert_eq!(after_three.render(&['.', '#']), "#.#.#.#");
+        assert_eq!(after_three.offset(), -2);
+    }
+
+    #[test]
+    fn from_rule_number_handles_large_numbers_and_states() {
+        // given
+        // 3^27 - 1, every neighbourhood becomes state 2, except the empty one
+        let all_twos = "7625597484986";
+        let all_but_empty = "7625597484984";
+
+        // when/then
+        assert!(Automaton::from_rule_number(3, 1, all_twos).is_ok());
+        assert!(Automaton::from_rule_number(3, 1, "7625597484987").is_err());
+        let automaton = Automaton::from_rule_number(3, 1, all_but_empty).unwrap();
+        let row = automaton.run(&Row::new(3, &[1], 5), 2).unwrap();
+        assert_eq!(row.render(&['0', '1', '2']), "22222");
+        assert_eq!(row.offset(), 3);
+        let error = Automaton::from_rule_number(3, 1, all_twos)
+            .unwrap()
+            .run(&row, 1);
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn from_table_uses_fallback() {
+        // given
+        // a cell moves one step to the right, everything else dies
+        let entries = vec![(vec![1, 0, 0], 1)];
+        let moving = Automaton::from_table(2, 1, &entries, Fallback::State(0)).unwrap();
+        let keeping = Automaton::from_table(2, 1, &entries, Fallback::Keep).unwrap();
+        let row = Row::new(2, &[1, 0, 0, 1], 0);
+
+        // when
+        let moved = moving.run(&row, 1_000_000_000).unwrap();
+        let kept = keeping.run(&row, 3).unwrap();
+
+        // then
+        assert_eq!(moved.render(&['.', '#']), "#..#");
+        assert_eq!(moved.offset(), 1_000_000_000);
+        // both cells grow to the right, the empty cell between them has a plant on both sides
+        assert_eq!(kept.render(&['.', '#']), "##.####");
+        assert!(Automaton::from_table(2, 1, &[(vec![1, 0], 1)], Fallback::Keep).is_err());
+        assert!(Automaton::from_table(2, 1, &entries, Fallback::State(2)).is_err());
+    }
+
+    #[test]
+    fn rows_are_packed_and_trimmed() {
+        // given
+        let cells: Vec<u8> = (0..100).map(|i| (i * 7 % 5) as u8).collect();
+
+        // when
+        let row = Row::new(5, &cells, -10);
+
+        // then
+        // 3 bits per cell, 21 cells in a word
+        assert_eq!(row.pattern.words.len(), 5);
+        assert_eq!(row.offset(), -9);
+        let unpacked: Vec<u8> = row.pattern.cells().collect();
+        assert_eq!(unpacked, cells[1..]);
+        assert_eq!(Row::new(2, &[0, 0], 3), Row::new(2, &[], 0));
+    }
+}
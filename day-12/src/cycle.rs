@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// the most cells that are remembered to recognise a repeating pattern
+const MAX_REMEMBERED_CELLS: usize = 1 << 20;
+
+// a row of cells that can move, the same pattern can show up at different offsets
+pub trait Generation: Sized {
+    type Pattern: Clone + Eq + Hash;
+
+    fn pattern(&self) -> &Self::Pattern;
+    fn offset(&self) -> i64;
+    fn with_offset(self, offset: i64) -> Self;
+    // the number of cells of the pattern
+    fn size(&self) -> usize;
+}
+
+// the generation after the given number of steps. Once a pattern repeats, all further
+// generations repeat with the same period, only the offset moves by the same amount in each
+// period. Only the generation and offset of each pattern are remembered, the generations after
+// the last full period are simulated.
+pub fn run<G: Generation>(
+    start: G,
+    generations: u64,
+    mut step: impl FnMut(&G) -> Result<G, String>,
+) -> Result<G, String> {
+    let mut first_seen: HashMap<G::Pattern, (u64, i64)> = HashMap::new();
+    let mut remembered_cells: usize = 0;
+    let mut current = start;
+    let mut generation: u64 = 0;
+    while generation < generations {
+        if let Some((first, first_offset)) = first_seen.get(current.pattern()).copied() {
+            let period = generation - first;
+            let periods = (generations - generation) / period;
+            let offset = current.offset() + periods as i64 * (current.offset() - first_offset);
+            current = current.with_offset(offset);
+            for _ in (generation + periods * period)..generations {
+                current = step(&current)?;
+            }
+            return Ok(current);
+        }
+        remembered_cells += current.size();
+        if remembered_cells > MAX_REMEMBERED_CELLS {
+            return Err(format!(
+                "No repeating pattern within {} generations, giving up to limit the memory use",
+                generation
+            ));
+        }
+        first_seen.insert(current.pattern().clone(), (generation, current.offset()));
+        current = step(&current)?;
+        generation += 1;
+    }
+    Ok(current)
+}
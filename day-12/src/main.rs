@@ -4,8 +4,17 @@ use std::env;
 use std::fs::read_to_string;
 use std::path::Path;
 
+mod automaton;
+mod cycle;
+
+use self::automaton::{Automaton, Fallback, Row};
+use self::cycle::Generation;
+
 fn main() -> Result<(), String> {
     let filename = env::args().nth(1).ok_or("No file name given.".to_owned())?;
+    if filename == "rule" {
+        return run_rule_number(&env::args().skip(2).collect::<Vec<String>>());
+    }
     let content = read_to_string(Path::new(&filename)).map_err(|e| e.to_string())?;
     let lines: Vec<&str> = content.lines().collect();
 
@@ -15,17 +24,34 @@ fn main() -> Result<(), String> {
     let initial_state =
         parse_initial_state(first_line).ok_or("Unable to parse initial state".to_owned())?;
     let rules = parse_rules(&lines[1..]);
+    // with "engine", the generations are calculated by the general automaton. Missing rules
+    // keep the pot unless a third argument gives the outcome ("#" or ".").
+    let use_engine = env::args().nth(2).as_deref() == Some("engine");
+    let fallback = match env::args().nth(3).as_deref() {
+        None => Fallback::Keep,
+        Some("#") => Fallback::State(1),
+        Some(".") => Fallback::State(0),
+        Some(other) => return Err(format!("Invalid outcome for missing rules: {}", other)),
+    };
+    let sum_after = |n_gen: u64| -> Result<i64, String> {
+        if use_engine {
+            let automaton = to_automaton(&rules, fallback)?;
+            Ok(automaton
+                .run(&to_row(&initial_state), n_gen)?
+                .sum_of_positions())
+        } else {
+            Ok(run_generations(&initial_state, &rules, n_gen)?.sum_plant_indices() as i64)
+        }
+    };
 
-    let after_20_gen = run_generations(&initial_state, &rules, 20);
-    let sum_after_20_gen = after_20_gen.sum_plant_indices();
+    let sum_after_20_gen = sum_after(20)?;
     println!(
         "Sum of plant indices after 20 generations: {}",
         sum_after_20_gen
     );
 
     let n_gen = 50000000000;
-    let after_n_gen = run_generations(&initial_state, &rules, n_gen);
-    let sum_after_n_gen = after_n_gen.sum_plant_indices();
+    let sum_after_n_gen = sum_after(n_gen)?;
     println!(
         "Sum of plant indices after {} generations: {}",
         n_gen, sum_after_n_gen
@@ -34,22 +60,60 @@ fn main() -> Result<(), String> {
     Ok(())
 }
 
-fn run_generations(state: &State, rules: &Rules, n_gen: u64) -> State {
-    let mut current_state = state.clone();
-    for i in 0..n_gen {
-        let next_state = current_state.next_gen(rules);
-        if next_state.pots == current_state.pots {
-            // The live pot pattern did not change. The offset may still change. But we can calculate how much the offset changes and just simulate what the state will look like later in O(1)
-            let offset_change = next_state.offset - current_state.offset;
-            let generations_to_come = (n_gen - 1 - i) as isize;
-            return State {
-                pots: next_state.pots,
-                offset: next_state.offset + generations_to_come * offset_change,
-            };
-        }
-        current_state = next_state;
+// arguments: rule number, number of states, radius, generations and the initial row as digits
+fn run_rule_number(args: &[String]) -> Result<(), String> {
+    let [number, states, radius, generations, pattern] = args else {
+        return Err("Expected: rule NUMBER STATES RADIUS GENERATIONS PATTERN".to_owned());
+    };
+    let states: u8 = states.parse().map_err(|_| "Invalid number of states")?;
+    let radius: usize = radius.parse().map_err(|_| "Invalid radius")?;
+    let generations: u64 = generations
+        .parse()
+        .map_err(|_| "Invalid number of generations")?;
+    let cells: Vec<u8> = pattern
+        .chars()
+        .map(|c| {
+            c.to_digit(states as u32)
+                .map(|digit| digit as u8)
+                .ok_or_else(|| format!("Invalid cell '{}' for {} states", c, states))
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+
+    let automaton = Automaton::from_rule_number(states, radius, number)?;
+    let row = automaton.run(&Row::new(states, &cells, 0), generations)?;
+    let symbols: Vec<char> = (0..states as u32)
+        .map(|state| char::from_digit(state, 36).unwrap_or('?'))
+        .collect();
+    println!("{} (first cell at {})", row.render(&symbols), row.offset());
+    println!("Sum of positions: {}", row.sum_of_positions());
+    Ok(())
+}
+
+// the day 12 rules as an automaton with 2 states and radius 2
+fn to_automaton(rules: &Rules, fallback: Fallback) -> Result<Automaton, String> {
+    let entries: Vec<(Vec<u8>, u8)> = rules
+        .iter()
+        .map(|(neighbours, plant)| {
+            let cells = neighbours.iter().map(|plant| *plant as u8).collect();
+            (cells, *plant as u8)
+        })
+        .collect();
+    Automaton::from_table(2, 2, &entries, fallback)
+}
+
+fn to_row(state: &State) -> Row {
+    let cells: Vec<u8> = state.pots.iter().map(|plant| *plant as u8).collect();
+    Row::new(2, &cells, -state.offset as i64)
+}
+
+fn run_generations(state: &State, rules: &Rules, n_gen: u64) -> Result<State, String> {
+    if rules.get(&[false; 5]) == Some(&true) {
+        return Err(
+            "The rules create plants from empty pots, there would be infinitely many plants"
+                .to_owned(),
+        );
     }
-    current_state
+    cycle::run(state.clone(), n_gen, |state| Ok(state.next_gen(rules)))
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -104,6 +168,29 @@ impl State {
     }
 }
 
+impl Generation for State {
+    type Pattern = VecDeque<bool>;
+
+    fn pattern(&self) -> &VecDeque<bool> {
+        &self.pots
+    }
+
+    fn offset(&self) -> i64 {
+        self.offset as i64
+    }
+
+    fn with_offset(self, offset: i64) -> State {
+        State {
+            offset: offset as isize,
+            ..self
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.pots.len()
+    }
+}
+
 fn parse_initial_state(line: &str) -> Option<State> {
     let pots: VecDeque<bool> = line
         .strip_prefix("initial state: ")?
@@ -179,4 +266,127 @@ mod test {
         assert_eq!(rules.len(), 2);
         assert_eq!(rules.get(&[false, true, false, true, false]), Some(&true));
     }
+
+    const EXAMPLE_RULES: [&str; 14] = [
+        "...## => #",
+        "..#.. => #",
+        ".#... => #",
+        ".#.#. => #",
+        ".#.## => #",
+        ".##.. => #",
+        ".#### => #",
+        "#.#.# => #",
+        "#.### => #",
+        "##.#. => #",
+        "##.## => #",
+        "###.. => #",
+        "###.# => #",
+        "####. => #",
+    ];
+
+    // all patterns that are not listed die
+    fn complete_rules(lines: &[&str]) -> Rules {
+        let mut rules = parse_rules(lines);
+        for pattern in 0..32 {
+            let neighbours: [bool; 5] = [16, 8, 4, 2, 1].map(|bit| pattern & bit != 0);
+            rules.entry(neighbours).or_insert(false);
+        }
+        rules
+    }
+
+    #[test]
+    fn run_generations_works_for_example() {
+        // given
+        let state = parse_initial_state("initial state: #..#.#..##......###...###").unwrap();
+        let rules = complete_rules(&EXAMPLE_RULES);
+
+        // when
+        let after_20_gen = run_generations(&state, &rules, 20).unwrap();
+
+        // then
+        assert_eq!(after_20_gen.sum_plant_indices(), 325);
+    }
+
+    #[test]
+    fn run_generations_matches_simulation_for_longer_periods() {
+        // given
+        let state = parse_initial_state("initial state: ##.#...##.#").unwrap();
+        // every rule set with a different sequence of outcomes
+        for seed in 0u32..40 {
+            let mut rules: Rules = HashMap::new();
+            for pattern in 1u32..32 {
+                let neighbours: [bool; 5] = [16, 8, 4, 2, 1].map(|bit| pattern & bit != 0);
+                let hash = pattern.wrapping_mul(2654435761).rotate_left(seed) ^ seed;
+                rules.insert(neighbours, hash & 4 != 0);
+            }
+
+            // when/then
+            let mut simulated = state.clone();
+            for n_gen in 0..60 {
+                let result = run_generations(&state, &rules, n_gen).unwrap();
+                assert_eq!(
+                    result.sum_plant_indices(),
+                    simulated.sum_plant_indices(),
+                    "seed {}, generation {}",
+                    seed,
+                    n_gen
+                );
+                simulated = simulated.next_gen(&rules);
+            }
+        }
+    }
+
+    #[test]
+    fn automaton_matches_run_generations() {
+        // given
+        let state = parse_initial_state("initial state: #..#.#..##......###...###").unwrap();
+        let rules = parse_rules(&EXAMPLE_RULES);
+        let automaton = to_automaton(&rules, Fallback::Keep).unwrap();
+        let complete = to_automaton(&rules, Fallback::State(0)).unwrap();
+
+        // when/then
+        for n_gen in [0, 1, 20, 100, 50000000000] {
+            let expected = run_generations(&state, &rules, n_gen).unwrap();
+            let row = automaton.run(&to_row(&state), n_gen).unwrap();
+            assert_eq!(row.sum_of_positions(), expected.sum_plant_indices() as i64);
+        }
+        let completed = complete_rules(&EXAMPLE_RULES);
+        let expected = run_generations(&state, &completed, 20).unwrap();
+        let row = complete.run(&to_row(&state), 20).unwrap();
+        assert_eq!(row.sum_of_positions(), expected.sum_plant_indices() as i64);
+        let empty_rule = parse_rules(&["..... => #"]);
+        assert!(to_automaton(&empty_rule, Fallback::Keep)
+            .unwrap()
+            .run(&to_row(&state), 1)
+            .is_err());
+    }
+
+    #[test]
+    fn run_generations_gives_up_on_endless_growth() {
+        // given
+        let state = parse_initial_state("initial state: #").unwrap();
+        // every pot next to a plant gets a plant, the row grows forever
+        let mut rules: Rules = HashMap::new();
+        for pattern in 1u32..32 {
+            let neighbours: [bool; 5] = [16, 8, 4, 2, 1].map(|bit| pattern & bit != 0);
+            rules.insert(neighbours, true);
+        }
+
+        // when
+        let result = run_generations(&state, &rules, 50000000000);
+
+        // then
+        assert!(result.is_err());
+        assert_eq!(
+            run_generations(&state, &rules, 100).unwrap().pots.len(),
+            401
+        );
+    }
+
+    #[test]
+    fn run_generations_rejects_plants_from_empty_pots() {
+        let state = parse_initial_state("initial state: #").unwrap();
+        let rules = parse_rules(&["..... => #"]);
+        assert!(run_generations(&state, &rules, 1).is_err());
+    }
 }
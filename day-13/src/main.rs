@@ -5,6 +5,11 @@ use std::fs::File;
 use std::io::{BufReader, Read};
 use std::path::Path;
 
+mod timeline;
+mod validate;
+
+use self::timeline::{render_frame, simulate};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum TrackPiece {
     Horizontal,
@@ -55,6 +60,8 @@ impl Direction {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Cart {
+    // carts are numbered in reading order of the map
+    id: usize,
     px: usize,
     py: usize,
     dir: Direction,
@@ -92,36 +99,7 @@ fn next_tick(mut carts: Vec<Cart>, tracks: &Tracks) -> Result<CartsTick, String>
             }
         }
     }
-    return Ok(CartsTick::Success(carts));
-}
-
-fn next_tick_remove_crashed(mut carts: Vec<Cart>, tracks: &Tracks) -> Result<Vec<Cart>, String> {
-    // That is some very nice mutable data structure you have there. Would be a shame if anything happened to it…
-    carts.sort_by(cmp_cart_pos);
-    let mut next_carts: Vec<Cart> = Vec::with_capacity(carts.len());
-    let mut i: usize = 0;
-    while i < carts.len() {
-        let cart = &carts[i];
-        let next_cart = move_cart(cart, tracks)?;
-        let dup_index = carts[i + 1..]
-            .iter()
-            .enumerate()
-            .filter(|(_, c)| c.px == next_cart.px && c.py == next_cart.py)
-            .map(|(i, _)| i)
-            .next();
-        if let Some(i) = dup_index {
-            carts.remove(i);
-        } else if next_carts
-            .iter()
-            .any(|c| c.px == next_cart.px && c.py == next_cart.py)
-        {
-            next_carts.retain(|c| c.px != next_cart.px || c.py != next_cart.py);
-        } else {
-            next_carts.push(next_cart);
-        }
-        i += 1;
-    }
-    return Ok(next_carts);
+    Ok(CartsTick::Success(carts))
 }
 
 fn move_cart(cart: &Cart, tracks: &Tracks) -> Result<Cart, String> {
@@ -145,13 +123,14 @@ fn move_cart(cart: &Cart, tracks: &Tracks) -> Result<Cart, String> {
         )
     })?;
 
-    let (dir, turn) = next_dir_for_cart(cart, &next_track);
-    return Ok(Cart {
+    let (dir, turn) = next_dir_for_cart(cart, next_track);
+    Ok(Cart {
+        id: cart.id,
         px: next_x,
         py: next_y,
         dir,
         turn,
-    });
+    })
 }
 
 fn next_dir_for_cart(cart: &Cart, track: &TrackPiece) -> (Direction, u8) {
@@ -212,31 +191,83 @@ fn run_until_crash(tracks: &Tracks, mut carts: Vec<Cart>) -> Result<(usize, usiz
     }
 }
 
-fn there_can_be_only_one(tracks: &Tracks, mut carts: Vec<Cart>) -> Result<(usize, usize), String> {
-    while carts.len() > 1 {
-        carts = next_tick_remove_crashed(carts, tracks)?;
-    }
-    return carts
-        .get(0)
+fn there_can_be_only_one(tracks: &Tracks, carts: Vec<Cart>) -> Result<(usize, usize), String> {
+    simulate(tracks, carts, &mut |_, _, _| ())?
+        .survivors
+        .first()
         .map(|cart| (cart.px, cart.py))
-        .ok_or_else(|| "All carts crashed!".to_owned());
+        .ok_or_else(|| "All carts crashed!".to_owned())
 }
 
 fn main() -> Result<(), String> {
     let filename = env::args().nth(1).ok_or("No file name given.".to_owned())?;
-    let content = read_file(&Path::new(&filename)).map_err(|e| e.to_string())?;
+    let content = read_file(Path::new(&filename)).map_err(|e| e.to_string())?;
     let lines: Vec<&str> = content.split('\n').collect();
     let (tracks, carts) = parse_map(&lines);
+    validate::check(&tracks, &carts)?;
 
-    let (crash_x, crash_y) = run_until_crash(&tracks, carts.clone())?;
-    println!("The first crash occurs at position {}×{}", crash_x, crash_y);
+    // the modes show what happens between the first crash and the last surviving cart
+    match env::args().nth(2).as_deref() {
+        None => {
+            let (crash_x, crash_y) = run_until_crash(&tracks, carts.clone())?;
+            println!("The first crash occurs at position {}×{}", crash_x, crash_y);
 
-    let (last_x, last_y) = there_can_be_only_one(&tracks, carts)?;
+            let (last_x, last_y) = there_can_be_only_one(&tracks, carts)?;
+            println!(
+                "The last surviving cart is at position {}×{}",
+                last_x, last_y
+            );
+        }
+        Some("events") => print_events(&tracks, carts)?,
+        Some("frames") => {
+            // the frames of the ticks from FIRST to LAST, tick 0 are the initial carts
+            let parse_tick = |arg: Option<String>, default: usize| {
+                arg.map_or(Ok(default), |arg| {
+                    arg.parse::<usize>()
+                        .map_err(|_| format!("Invalid tick '{}'", arg))
+                })
+            };
+            let first = parse_tick(env::args().nth(3), 0)?;
+            let last = parse_tick(env::args().nth(4), usize::MAX)?;
+            simulate(&tracks, carts, &mut |tick, carts, collisions| {
+                if (first..=last).contains(&tick) {
+                    println!("Tick {}:", tick);
+                    print!("{}", render_frame(&tracks, carts, collisions));
+                }
+            })?;
+        }
+        Some(mode) => return Err(format!("Unknown mode '{}'", mode)),
+    }
+
+    Ok(())
+}
+
+fn print_events(tracks: &Tracks, carts: Vec<Cart>) -> Result<(), String> {
+    let timeline = simulate(tracks, carts, &mut |_, _, _| ())?;
+    for collision in timeline.collisions.iter() {
+        println!(
+            "Tick {}: cart {} hits cart {} at position {}×{}",
+            collision.tick, collision.carts[0], collision.carts[1], collision.px, collision.py
+        );
+    }
+    for (id, path) in timeline.paths.iter().enumerate() {
+        if let (Some((start_x, start_y)), Some((end_x, end_y))) = (path.first(), path.last()) {
+            println!(
+                "Cart {} moves {} times from {}×{} to {}×{}",
+                id,
+                path.len() - 1,
+                start_x,
+                start_y,
+                end_x,
+                end_y
+            );
+        }
+    }
     println!(
-        "The last surviving cart is at position {}×{}",
-        last_x, last_y
+        "{} carts left after {} ticks",
+        timeline.survivors.len(),
+        timeline.ticks
     );
-
     Ok(())
 }
 
@@ -245,12 +276,12 @@ fn read_file(path: &Path) -> std::io::Result<String> {
     let mut bufr = BufReader::new(ifile);
     let mut result = String::with_capacity(2048);
     bufr.read_to_string(&mut result)?;
-    return Ok(result);
+    Ok(result)
 }
 
 fn parse_map(lines: &[&str]) -> (Tracks, Vec<Cart>) {
     let mut tracks: Tracks =
-        Tracks::with_capacity(lines.len() * lines.get(0).map(|l| l.len()).unwrap_or(0));
+        Tracks::with_capacity(lines.len() * lines.first().map(|l| l.len()).unwrap_or(0));
     let mut carts: Vec<Cart> = Vec::with_capacity(128);
     for (y, line) in lines.iter().enumerate() {
         for (x, c) in line.chars().enumerate() {
@@ -259,6 +290,7 @@ fn parse_map(lines: &[&str]) -> (Tracks, Vec<Cart>) {
             }
             if let Some(dir) = Direction::from_char(c) {
                 carts.push(Cart {
+                    id: carts.len(),
                     dir,
                     px: x,
                     py: y,
@@ -267,7 +299,7 @@ fn parse_map(lines: &[&str]) -> (Tracks, Vec<Cart>) {
             }
         }
     }
-    return (tracks, carts);
+    (tracks, carts)
 }
 
 #[cfg(test)]
@@ -0,0 +1,247 @@
+use super::{cmp_cart_pos, move_cart, Cart, Direction, TrackPiece, Tracks};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Collision {
+    // ticks are counted from 1
+    pub tick: usize,
+    pub px: usize,
+    pub py: usize,
+    // the id of the cart that moved and the id of the cart it hit
+    pub carts: [usize; 2],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Timeline {
+    pub ticks: usize,
+    pub collisions: Vec<Collision>,
+    // the positions of each cart by id, starting with its initial position and ending where it
+    // crashed or where it was after the last tick
+    pub paths: Vec<Vec<(usize, usize)>>,
+    pub survivors: Vec<Cart>,
+}
+
+// move all carts once, crashed carts are removed right away and do not move anymore
+fn tick_remove_crashed(
+    mut carts: Vec<Cart>,
+    tracks: &Tracks,
+    tick: usize,
+) -> Result<(Vec<Cart>, Vec<Collision>), String> {
+    carts.sort_by(cmp_cart_pos);
+    let mut crashed = vec![false; carts.len()];
+    let mut collisions: Vec<Collision> = Vec::new();
+    for i in 0..carts.len() {
+        if crashed[i] {
+            continue;
+        }
+        carts[i] = move_cart(&carts[i], tracks)?;
+        let hit = (0..carts.len()).find(|j| {
+            *j != i && !crashed[*j] && carts[*j].px == carts[i].px && carts[*j].py == carts[i].py
+        });
+        if let Some(j) = hit {
+            crashed[i] = true;
+            crashed[j] = true;
+            collisions.push(Collision {
+                tick,
+                px: carts[i].px,
+                py: carts[i].py,
+                carts: [carts[i].id, carts[j].id],
+            });
+        }
+    }
+    let remaining: Vec<Cart> = carts
+        .into_iter()
+        .zip(crashed)
+        .filter(|(_, crashed)| !crashed)
+        .map(|(cart, _)| cart)
+        .collect();
+    Ok((remaining, collisions))
+}
+
+// gets the tick, the remaining carts and the collisions of that tick
+type OnTick<'a> = dyn FnMut(usize, &[Cart], &[Collision]) + 'a;
+
+// run until at most one cart is left, on_tick is called with tick 0 for the initial carts
+pub fn simulate(
+    tracks: &Tracks,
+    mut carts: Vec<Cart>,
+    on_tick: &mut OnTick,
+) -> Result<Timeline, String> {
+    let n_carts = carts.iter().map(|cart| cart.id + 1).max().unwrap_or(0);
+    let mut paths: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n_carts];
+    for cart in carts.iter() {
+        paths[cart.id].push((cart.px, cart.py));
+    }
+    on_tick(0, &carts, &[]);
+
+    let mut collisions: Vec<Collision> = Vec::new();
+    let mut tick = 0;
+    while carts.len() > 1 {
+        tick += 1;
+        let (remaining, tick_collisions) = tick_remove_crashed(carts, tracks, tick)?;
+        for cart in remaining.iter() {
+            paths[cart.id].push((cart.px, cart.py));
+        }
+        for collision in tick_collisions.iter() {
+            for id in collision.carts {
+                paths[id].push((collision.px, collision.py));
+            }
+        }
+        on_tick(tick, &remaining, &tick_collisions);
+        collisions.extend(tick_collisions);
+        carts = remaining;
+    }
+    Ok(Timeline {
+        ticks: tick,
+        collisions,
+        paths,
+        survivors: carts,
+    })
+}
+
+fn track_char(piece: TrackPiece) -> char {
+    match piece {
+        TrackPiece::Horizontal => '-',
+        TrackPiece::Vertical => '|',
+        TrackPiece::TurnSlash => '/',
+        TrackPiece::TurnBSlash => '\\',
+        TrackPiece::Intersection => '+',
+    }
+}
+
+fn cart_char(dir: Direction) -> char {
+    match dir {
+        Direction::Up => '^',
+        Direction::Down => 'v',
+        Direction::Left => '<',
+        Direction::Right => '>',
+    }
+}
+
+// the tracks in the format of the input with the carts on them, collisions are shown as 'X'
+pub fn render_frame(tracks: &Tracks, carts: &[Cart], collisions: &[Collision]) -> String {
+    let width = tracks.keys().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let height = tracks.keys().map(|(_, y)| y + 1).max().unwrap_or(0);
+    let mut grid: Vec<Vec<char>> = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    tracks
+                        .get(&(x, y))
+                        .map(|piece| track_char(*piece))
+                        .unwrap_or(' ')
+                })
+                .collect()
+        })
+        .collect();
+    for cart in carts {
+        grid[cart.py][cart.px] = cart_char(cart.dir);
+    }
+    for collision in collisions {
+        grid[collision.py][collision.px] = 'X';
+    }
+    grid.iter()
+        .map(|row| {
+            let line: String = row.iter().collect();
+            line.trim_end().to_owned() + "\n"
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse_map;
+    use super::*;
+
+    const CRASH_EXAMPLE: [&str; 6] = [
+        r"/->-\        ",
+        r"|   |  /----\",
+        r"| /-+--+-\  |",
+        r"| | |  | v  |",
+        r"\-+-/  \-+--/",
+        r"  \------/   ",
+    ];
+
+    #[test]
+    fn simulate_logs_collisions_and_paths() {
+        // given
+        let lines = &[
+            r"/>-<\  ", r"|   |  ", r"| /<+-\", r"| | | v", r"\>+</ |", r"  |   ^", r"  \<->/",
+        ];
+        let (tracks, carts) = parse_map(lines);
+
+        // when
+        let timeline = simulate(&tracks, carts, &mut |_, _, _| ()).unwrap();
+
+        // then
+        assert_eq!(timeline.ticks, 3);
+        assert_eq!(
+            timeline.collisions,
+            vec![
+                Collision {
+                    tick: 1,
+                    px: 2,
+                    py: 0,
+                    carts: [1, 0]
+                },
+                Collision {
+                    tick: 1,
+                    px: 2,
+                    py: 4,
+                    carts: [5, 4]
+                },
+                Collision {
+                    tick: 1,
+                    px: 6,
+                    py: 4,
+                    carts: [6, 3]
+                },
+                Collision {
+                    tick: 3,
+                    px: 2,
+                    py: 4,
+                    carts: [7, 2]
+                },
+            ]
+        );
+        assert_eq!(timeline.survivors.len(), 1);
+        let survivor = &timeline.survivors[0];
+        assert_eq!((survivor.id, survivor.px, survivor.py), (8, 6, 4));
+        assert_eq!(timeline.paths[8], vec![(5, 6), (6, 6), (6, 5), (6, 4)]);
+        assert_eq!(timeline.paths[0], vec![(1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn render_frame_shows_carts_and_crashes() {
+        // given
+        let (tracks, carts) = parse_map(&CRASH_EXAMPLE);
+        let mut frames: Vec<String> = Vec::new();
+
+        // when
+        let timeline = simulate(&tracks, carts, &mut |_, carts, collisions| {
+            frames.push(render_frame(&tracks, carts, collisions))
+        })
+        .unwrap();
+
+        // then
+        assert_eq!(timeline.ticks, 14);
+        assert_eq!(frames.len(), 15);
+        let initial: String = CRASH_EXAMPLE
+            .iter()
+            .map(|line| line.trim_end().to_owned() + "\n")
+            .collect();
+        assert_eq!(frames[0], initial);
+        assert_eq!(
+            frames[14],
+            [
+                r"/---\",
+                r"|   |  /----\",
+                r"| /-+--+-\  |",
+                r"| | |  X |  |",
+                r"\-+-/  \-+--/",
+                r"  \------/",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
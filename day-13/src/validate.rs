@@ -0,0 +1,302 @@
+use super::{Cart, Direction, TrackPiece, Tracks};
+use std::collections::HashMap;
+use std::fmt;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Problem {
+    pub x: usize,
+    pub y: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}×{}: {}", self.x, self.y, self.message)
+    }
+}
+
+// the sides of a piece that connect to other pieces, one bit per direction
+type Sides = u8;
+
+fn side(dir: Direction) -> Sides {
+    match dir {
+        Direction::Up => 1,
+        Direction::Down => 2,
+        Direction::Left => 4,
+        Direction::Right => 8,
+    }
+}
+
+fn opposite(dir: Direction) -> Direction {
+    match dir {
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+    }
+}
+
+fn neighbour(x: usize, y: usize, dir: Direction) -> Option<(usize, usize)> {
+    match dir {
+        Direction::Up => Some((x, y.checked_sub(1)?)),
+        Direction::Down => Some((x, y + 1)),
+        Direction::Left => Some((x.checked_sub(1)?, y)),
+        Direction::Right => Some((x + 1, y)),
+    }
+}
+
+// the ways a piece can connect, a curve can connect in two ways
+fn possible_sides(piece: TrackPiece) -> Vec<Sides> {
+    let up = side(Direction::Up);
+    let down = side(Direction::Down);
+    let left = side(Direction::Left);
+    let right = side(Direction::Right);
+    match piece {
+        TrackPiece::Horizontal => vec![left | right],
+        TrackPiece::Vertical => vec![up | down],
+        TrackPiece::TurnSlash => vec![down | right, up | left],
+        TrackPiece::TurnBSlash => vec![down | left, up | right],
+        TrackPiece::Intersection => vec![up | down | left | right],
+    }
+}
+
+// Decide how the curves connect: a way is dropped if a neighbour can not agree with it, i.e. the
+// curve connects to a neighbour that can not connect back or the other way round. This is
+// repeated until nothing changes, as dropping a way of one curve can decide a curve next to it.
+fn resolve_curves(tracks: &Tracks) -> HashMap<(usize, usize), Vec<Sides>> {
+    let mut ways: HashMap<(usize, usize), Vec<Sides>> = tracks
+        .iter()
+        .map(|(pos, piece)| (*pos, possible_sides(*piece)))
+        .collect();
+    let mut curves: Vec<(usize, usize)> = tracks
+        .iter()
+        .filter(|(_, piece)| **piece == TrackPiece::TurnSlash || **piece == TrackPiece::TurnBSlash)
+        .map(|(pos, _)| *pos)
+        .collect();
+    curves.sort();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (x, y) in curves.iter() {
+            let remaining: Vec<Sides> = ways[&(*x, *y)]
+                .iter()
+                .copied()
+                .filter(|sides| {
+                    DIRECTIONS.iter().all(|dir| {
+                        let connects = sides & side(*dir) != 0;
+                        match neighbour(*x, *y, *dir).and_then(|pos| ways.get(&pos)) {
+                            None => !connects,
+                            // a curve that fits nowhere is reported on its own
+                            Some(other) if other.is_empty() => true,
+                            Some(other) => other.iter().any(|other_sides| {
+                                (other_sides & side(opposite(*dir)) != 0) == connects
+                            }),
+                        }
+                    })
+                })
+                .collect();
+            if remaining.len() != ways[&(*x, *y)].len() {
+                ways.insert((*x, *y), remaining);
+                changed = true;
+            }
+        }
+    }
+    ways
+}
+
+// all problems of the map, sorted by position. A map without problems has no dead ends, every
+// curve connects in exactly one way and every cart points along the track it is on.
+pub fn validate(tracks: &Tracks, carts: &[Cart]) -> Vec<Problem> {
+    let ways = resolve_curves(tracks);
+    let mut problems: Vec<Problem> = Vec::new();
+    for ((x, y), piece) in tracks.iter() {
+        let mut report = |message: String| {
+            problems.push(Problem {
+                x: *x,
+                y: *y,
+                message,
+            })
+        };
+        let sides = match ways[&(*x, *y)][..] {
+            [sides] => sides,
+            [] => {
+                report(format!("The curve {:?} fits none of its neighbours", piece));
+                continue;
+            }
+            _ => {
+                report(format!("The curve {:?} can connect in two ways", piece));
+                continue;
+            }
+        };
+        for dir in DIRECTIONS.iter().filter(|dir| sides & side(**dir) != 0) {
+            match neighbour(*x, *y, *dir).and_then(|pos| ways.get(&pos)) {
+                None => report(format!("The {:?} track ends towards {:?}", piece, dir)),
+                Some(other) => {
+                    if let [other_sides] = other[..] {
+                        if other_sides & side(opposite(*dir)) == 0 {
+                            report(format!(
+                                "The {:?} track is not connected towards {:?}",
+                                piece, dir
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for cart in carts {
+        let fits = match ways.get(&(cart.px, cart.py)).map(|ways| &ways[..]) {
+            None => Err("The cart is not on a track".to_owned()),
+            Some([sides]) => {
+                let along = side(cart.dir) | side(opposite(cart.dir));
+                if sides & along == along {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "The cart facing {:?} is on a {:?} piece",
+                        cart.dir,
+                        tracks[&(cart.px, cart.py)]
+                    ))
+                }
+            }
+            // the curve is already reported
+            Some(_) => Ok(()),
+        };
+        if let Err(message) = fits {
+            problems.push(Problem {
+                x: cart.px,
+                y: cart.py,
+                message,
+            });
+        }
+    }
+
+    problems.sort_by_key(|problem| (problem.y, problem.x));
+    problems
+}
+
+// an error with every problem of the map, so that broken maps are never simulated
+pub fn check(tracks: &Tracks, carts: &[Cart]) -> Result<(), String> {
+    let problems = validate(tracks, carts);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let lines: Vec<String> = problems.iter().map(|problem| problem.to_string()).collect();
+    Err(format!(
+        "Found {} problems in the track map:\n{}",
+        problems.len(),
+        lines.join("\n")
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse_map;
+    use super::*;
+
+    #[test]
+    fn validate_accepts_examples() {
+        // given
+        let crash_example = &[
+            r"/->-\        ",
+            r"|   |  /----\",
+            r"| /-+--+-\  |",
+            r"| | |  | v  |",
+            r"\-+-/  \-+--/",
+            r"  \------/   ",
+        ];
+        let survivor_example = &[
+            r"/>-<\  ", r"|   |  ", r"| /<+-\", r"| | | v", r"\>+</ |", r"  |   ^", r"  \<->/",
+        ];
+        let tiny_loop = &[r"/\", r"\/"];
+
+        // when/then
+        for lines in [&crash_example[..], &survivor_example[..], &tiny_loop[..]] {
+            let (tracks, carts) = parse_map(lines);
+            assert_eq!(validate(&tracks, &carts), vec![]);
+        }
+    }
+
+    #[test]
+    fn validate_accepts_adjacent_tracks_and_curves() {
+        // given
+        let side_by_side = &[r"/--\/--\", r"|  ||  |", r"\--/\--/"];
+        let stacked = &[r"/--\", r"|  |", r"\--/", r"/--\", r"|  |", r"\--/"];
+        let nested = &[r"/----\", r"|/--\|", r"||  ||", r"|\--/|", r"\----/"];
+        let zigzag = &[r"/-\", r"| \-\", r"\---/"];
+        let parallel = &[r"/----\", r"\----/", r"/----\", r"\-->-/"];
+
+        // when/then
+        for lines in [
+            &side_by_side[..],
+            &stacked[..],
+            &nested[..],
+            &zigzag[..],
+            &parallel[..],
+        ] {
+            let (tracks, carts) = parse_map(lines);
+            assert_eq!(validate(&tracks, &carts), vec![], "{:?}", lines);
+        }
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        // given
+        let lines = &[r"/-\   ", r"| | |-", r"\-+-/ ", r"  |  /"];
+        let (tracks, mut carts) = parse_map(lines);
+        carts.push(Cart {
+            id: carts.len(),
+            px: 1,
+            py: 0,
+            dir: Direction::Up,
+            turn: 0,
+        });
+
+        // when
+        let problems: Vec<String> = validate(&tracks, &carts)
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect();
+
+        // then
+        assert_eq!(
+            problems,
+            vec![
+                "1×0: The cart facing Up is on a Horizontal piece",
+                "4×1: The Vertical track ends towards Up",
+                "5×1: The Horizontal track is not connected towards Left",
+                "5×1: The Horizontal track ends towards Right",
+                "2×3: The Vertical track ends towards Down",
+                "5×3: The curve TurnSlash fits none of its neighbours",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_lists_every_problem() {
+        // given
+        let (tracks, carts) = parse_map(&[r"/->-\", r"|   |", r"\---+"]);
+        let (good_tracks, good_carts) = parse_map(&[r"/->-\", r"|   |", r"\---/"]);
+
+        // when
+        let error = check(&tracks, &carts).unwrap_err();
+
+        // then
+        assert_eq!(
+            error,
+            "Found 2 problems in the track map:
+4×2: The Intersection track ends towards Down
+4×2: The Intersection track ends towards Right"
+        );
+        assert_eq!(check(&good_tracks, &good_carts), Ok(()));
+    }
+}